@@ -0,0 +1,102 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, ToTokens};
+
+/// Name of the custom section the ABI of a contract is emitted into.
+const ABI_SECTION: &str = "vm_abi";
+
+/// Emits the ABI record of an export into the `vm_abi` custom section: the
+/// length of a JSON description as a little endian `u32`, the JSON itself,
+/// then the size and alignment of the archived form of each argument and of
+/// the return value, as pairs of `u32`. The linker concatenates the records
+/// of all exports.
+pub(crate) fn record(
+    kind: &str,
+    name: &syn::Ident,
+    arg_tys: &[&syn::Type],
+    output: &syn::ReturnType,
+) -> TokenStream {
+    let args: Vec<String> = arg_tys
+        .iter()
+        .map(|ty| json_string(&type_name(ty)))
+        .collect();
+    let return_ty: syn::Type = match output {
+        syn::ReturnType::Default => syn::parse_quote!(()),
+        syn::ReturnType::Type(_, ty) => (**ty).clone(),
+    };
+
+    let json = format!(
+        "{{\"name\":{},\"kind\":\"{}\",\"args\":[{}],\"returns\":{}}}",
+        json_string(&name.to_string()),
+        kind,
+        args.join(","),
+        json_string(&type_name(&return_ty)),
+    );
+    let len = json.len();
+    let json_len = len as u32;
+    let bytes = syn::LitByteStr::new(json.as_bytes(), proc_macro2::Span::call_site());
+
+    let tys: Vec<&syn::Type> = arg_tys.iter().copied().chain(Some(&return_ty)).collect();
+    let layouts = tys.len();
+
+    // the exact name keeps exports differing only by case apart
+    let static_ident = format_ident!("__VM_ABI_{}", name);
+    let record_ident = format_ident!("__VM_ABI_RECORD_{}", name);
+
+    quote! {
+        #[doc(hidden)]
+        #[allow(dead_code, non_camel_case_types)]
+        #[repr(C, packed)]
+        pub struct #record_ident {
+            json_len: u32,
+            json: [u8; #len],
+            layouts: [[u32; 2]; #layouts],
+        }
+
+        #[doc(hidden)]
+        #[used]
+        #[allow(non_upper_case_globals)]
+        #[link_section = #ABI_SECTION]
+        pub static #static_ident: #record_ident = #record_ident {
+            json_len: #json_len,
+            json: *#bytes,
+            layouts: [#([
+                core::mem::size_of::<primitives::rkyv::Archived<#tys>>() as u32,
+                core::mem::align_of::<primitives::rkyv::Archived<#tys>>() as u32,
+            ]),*],
+        };
+    }
+}
+
+fn type_name(ty: &syn::Type) -> String {
+    ty.to_token_stream().to_string().replace(' ', "")
+}
+
+fn json_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::record;
+
+    fn record_of(name: &str) -> String {
+        let name = syn::Ident::new(name, proc_macro2::Span::call_site());
+        record("function", &name, &[], &syn::ReturnType::Default).to_string()
+    }
+
+    #[test]
+    fn names_differing_by_case_do_not_collide() {
+        let lower = record_of("balance");
+        let upper = record_of("Balance");
+
+        assert!(lower.contains("static __VM_ABI_balance :"));
+        assert!(upper.contains("static __VM_ABI_Balance :"));
+        assert!(!lower.contains("__VM_ABI_Balance"));
+    }
+}
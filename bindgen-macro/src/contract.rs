@@ -0,0 +1,114 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+
+use crate::abi;
+
+/// Generates the exports of the methods of a contract state, `&self` methods
+/// as queries and `&mut self` methods as transactions.
+pub(crate) fn expand(input: syn::ItemImpl) -> syn::Result<TokenStream> {
+    if !input.generics.params.is_empty() {
+        let err = "generic contracts are not supported";
+        return Err(syn::Error::new_spanned(&input.generics, err));
+    }
+    if let Some((_, path, _)) = &input.trait_ {
+        let err = "trait implementations are not supported";
+        return Err(syn::Error::new_spanned(path, err));
+    }
+
+    let state_ty = &input.self_ty;
+    let mut exports = vec![];
+
+    for item in &input.items {
+        let method = match item {
+            syn::ImplItem::Method(method) => method,
+            _ => continue,
+        };
+        if !matches!(method.vis, syn::Visibility::Public(_)) {
+            continue;
+        }
+        let sig = &method.sig;
+
+        let mutable = match sig.receiver() {
+            Some(syn::FnArg::Receiver(receiver)) if receiver.reference.is_some() => {
+                receiver.mutability.is_some()
+            }
+            Some(receiver) => {
+                let err = "contract methods must take `&self` or `&mut self`";
+                return Err(syn::Error::new_spanned(receiver, err));
+            }
+            None => continue,
+        };
+
+        let mut arg_idents = vec![];
+        let mut arg_tys = vec![];
+        for (i, arg) in sig.inputs.iter().skip(1).enumerate() {
+            if let syn::FnArg::Typed(pat_ty) = arg {
+                arg_idents.push(format_ident!("arg{}", i));
+                arg_tys.push(&*pat_ty.ty);
+            }
+        }
+
+        let fn_name = &sig.ident;
+        let wasm_ident = format_ident!("__vm_{}", fn_name);
+
+        let body = if mutable {
+            quote! {
+                let mut state = state;
+                let ret = state.#fn_name(#(#arg_idents),*);
+
+                let state_len = match primitives::q_return(&state, store.clone(), &mut scratch[..]) {
+                    Ok(len) => len,
+                    Err(code) => return code,
+                };
+                match primitives::q_return(&ret, store, &mut scratch[state_len as usize..]) {
+                    Ok(data_len) => primitives::encode_lengths(state_len, data_len),
+                    Err(code) => code,
+                }
+            }
+        } else {
+            quote! {
+                let ret = state.#fn_name(#(#arg_idents),*);
+
+                match primitives::q_return(&ret, store, scratch) {
+                    Ok(data_len) => primitives::encode_lengths(0, data_len),
+                    Err(code) => code,
+                }
+            }
+        };
+
+        let kind = if mutable { "transaction" } else { "query" };
+        let abi = abi::record(kind, fn_name, &arg_tys, &sig.output);
+
+        exports.push(quote! {
+            #abi
+
+            #[doc(hidden)]
+            #[no_mangle]
+            fn #wasm_ident(written_state: u32, written_data: u32) -> u64 {
+                let scratch = unsafe { &mut primitives::SCRATCH[..] };
+                let store = primitives::guest_store();
+
+                let (state, (#(#arg_idents),*)): (#state_ty, (#(#arg_tys),*)) =
+                    primitives::get_state_arg(written_state, written_data, &*scratch, store.clone());
+
+                #body
+            }
+        });
+    }
+
+    Ok(quote! {
+
+        #input
+
+        #[cfg(target_family = "wasm")]
+        const _: () = {
+            #(#exports)*
+        };
+    })
+}
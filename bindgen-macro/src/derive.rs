@@ -0,0 +1,141 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::Token;
+
+/// Options given in a `#[query(...)]` or `#[transaction(...)]` attribute.
+#[derive(Default)]
+struct Options {
+    name: Option<syn::LitStr>,
+    returns: Option<syn::Type>,
+}
+
+/// A single `key = value` option.
+enum OptionArg {
+    Name(syn::LitStr),
+    Returns(Box<syn::Type>),
+}
+
+impl Parse for OptionArg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let key: syn::Ident = input.parse()?;
+        input.parse::<Token![=]>()?;
+
+        match key.to_string().as_str() {
+            "name" => Ok(OptionArg::Name(input.parse()?)),
+            "returns" => Ok(OptionArg::Returns(input.parse()?)),
+            _ => Err(syn::Error::new_spanned(key, "expected `name` or `returns`")),
+        }
+    }
+}
+
+impl Options {
+    fn from_attrs(attrs: &[syn::Attribute], attr_name: &str) -> syn::Result<Self> {
+        let mut options = Options::default();
+
+        for attr in attrs.iter().filter(|attr| attr.path.is_ident(attr_name)) {
+            let parsed =
+                attr.parse_args_with(Punctuated::<OptionArg, Token![,]>::parse_terminated)?;
+            for option in parsed {
+                match option {
+                    OptionArg::Name(name) => options.name = Some(name),
+                    OptionArg::Returns(returns) => options.returns = Some(*returns),
+                }
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+/// Implements `trait_name` for the type, with `NAME` defaulting to the type
+/// name in snake case and `Return` to `()`.
+pub(crate) fn expand(
+    input: syn::DeriveInput,
+    trait_name: &str,
+    attr_name: &str,
+) -> syn::Result<TokenStream> {
+    let options = Options::from_attrs(&input.attrs, attr_name)?;
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let name = match options.name {
+        Some(name) => name.value(),
+        None => snake_case(&ident.to_string()),
+    };
+    let returns = match options.returns {
+        Some(returns) => quote!(#returns),
+        None => quote!(()),
+    };
+    let trait_ident = syn::Ident::new(trait_name, proc_macro2::Span::call_site());
+
+    // generic types can only be checked once instantiated, by the bounds of
+    // the typed host API
+    let assertion = if input.generics.params.is_empty() {
+        quote!(const _: fn() = primitives::assert_archivable::<#ident>;)
+    } else {
+        quote!()
+    };
+
+    Ok(quote! {
+        impl #impl_generics primitives::#trait_ident for #ident #ty_generics #where_clause {
+            const NAME: &'static str = #name;
+
+            type Return = #returns;
+        }
+
+        #assertion
+    })
+}
+
+/// Converts a type name from `CamelCase` to `snake_case`. A run of capitals
+/// is a single word, ending before a capital followed by a lowercase letter,
+/// so `HTTPRequest` becomes `http_request`.
+fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut snake = String::with_capacity(name.len() + 4);
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() {
+            let prev = i.checked_sub(1).map(|i| chars[i]);
+            let next = chars.get(i + 1);
+            let word_start = match prev {
+                Some(prev) if prev.is_uppercase() => next.map_or(false, |c| c.is_lowercase()),
+                Some(_) => true,
+                None => false,
+            };
+            if word_start {
+                snake.push('_');
+            }
+            snake.extend(c.to_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
+
+#[cfg(test)]
+mod tests {
+    use super::snake_case;
+
+    #[test]
+    fn camel_case_words() {
+        assert_eq!(snake_case("Transfer"), "transfer");
+        assert_eq!(snake_case("GetBalance"), "get_balance");
+    }
+
+    #[test]
+    fn runs_of_capitals() {
+        assert_eq!(snake_case("HTTPRequest"), "http_request");
+        assert_eq!(snake_case("GetHTTP"), "get_http");
+        assert_eq!(snake_case("ID"), "id");
+    }
+}
@@ -7,23 +7,60 @@
 use proc_macro::TokenStream;
 use quote::{format_ident, quote};
 
+mod abi;
+mod contract;
+mod derive;
+
+/// Exports a function to the host as `__vm_<name>`.
+///
+/// Functions without arguments and outputs are exported as they are.
+/// Otherwise the arguments are read as archived values from the scratch
+/// buffer, and the output is serialized back into it. Typed functions
+/// require `primitives` to be built without the `host` feature.
 #[proc_macro_attribute]
 pub fn bindgen(_attrs: TokenStream, input: TokenStream) -> TokenStream {
     let input = syn::parse_macro_input!(input as syn::ItemFn);
     let sig = &input.sig;
 
-    if sig.inputs.len() > 0 {
-        let err = "function arguments are not supported";
-        return syn::Error::new_spanned(&sig, err).to_compile_error().into();
-    }
+    let fn_name = sig.ident.clone();
+    let wasm_ident = format_ident!("__vm_{}", fn_name);
 
-    if !matches!(sig.output, syn::ReturnType::Default) {
-        let err = "function outputs are not supported";
-        return syn::Error::new_spanned(&sig, err).to_compile_error().into();
+    let mut arg_idents = vec![];
+    let mut arg_tys = vec![];
+    for (i, arg) in sig.inputs.iter().enumerate() {
+        match arg {
+            syn::FnArg::Typed(pat_ty) => {
+                arg_idents.push(format_ident!("arg{}", i));
+                arg_tys.push(&*pat_ty.ty);
+            }
+            syn::FnArg::Receiver(_) => {
+                let err = "methods are not supported";
+                return syn::Error::new_spanned(&arg, err).to_compile_error().into();
+            }
+        }
     }
 
-    let fn_name = sig.ident.clone();
-    let wasm_ident = format_ident!("__vm_{}", fn_name);
+    let abi = abi::record("function", &fn_name, &arg_tys, &sig.output);
+
+    if arg_idents.is_empty() && matches!(sig.output, syn::ReturnType::Default) {
+        let gen = quote! {
+
+            #input
+
+            #[cfg(target_family = "wasm")]
+            const _: () = {
+
+                #abi
+
+                #[doc(hidden)]
+                #[no_mangle]
+                fn #wasm_ident() {
+                    #fn_name();
+                }
+            };
+        };
+        return gen.into();
+    }
 
     let gen = quote! {
 
@@ -32,12 +69,68 @@ pub fn bindgen(_attrs: TokenStream, input: TokenStream) -> TokenStream {
         #[cfg(target_family = "wasm")]
         const _: () = {
 
+            #abi
+
             #[doc(hidden)]
             #[no_mangle]
-            fn #wasm_ident() {
-                #fn_name();
+            fn #wasm_ident(written_state: u32, written_data: u32) -> u64 {
+                let scratch = unsafe { &mut primitives::SCRATCH[..] };
+                let store = primitives::guest_store();
+
+                let ((), (#(#arg_idents),*)): ((), (#(#arg_tys),*)) =
+                    primitives::get_state_arg(written_state, written_data, &*scratch, store.clone());
+
+                let ret = #fn_name(#(#arg_idents),*);
+
+                match primitives::q_return(&ret, store, scratch) {
+                    Ok(written) => primitives::encode_lengths(0, written),
+                    Err(code) => code,
+                }
             }
         };
     };
     gen.into()
 }
+
+/// Exports the methods of a contract state, given its `impl` block.
+///
+/// Public `&self` methods are exported as queries and public `&mut self`
+/// methods as transactions, both as `__vm_<name>`. The state and arguments
+/// are read from the scratch buffer; transactions write the new state back
+/// into it, followed by the return value.
+#[proc_macro_attribute]
+pub fn contract(_attrs: TokenStream, input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as syn::ItemImpl);
+
+    match contract::expand(input) {
+        Ok(gen) => gen.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Implements `primitives::Query` for a type.
+///
+/// `NAME` defaults to the type name in snake case, matching the export of
+/// the contract method of the same name, and `Return` to `()`. Both can be
+/// set with `#[query(name = "...", returns = Type)]`.
+#[proc_macro_derive(Query, attributes(query))]
+pub fn derive_query(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+
+    match derive::expand(input, "Query", "query") {
+        Ok(gen) => gen.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Implements `primitives::Transaction` for a type, the same way
+/// [`macro@Query`] does, with options set by `#[transaction(...)]`.
+#[proc_macro_derive(Transaction, attributes(transaction))]
+pub fn derive_transaction(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+
+    match derive::expand(input, "Transaction", "transaction") {
+        Ok(gen) => gen.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
@@ -0,0 +1,85 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use vm::{Config, HostCosts, VMError};
+use vm_test::{assert_vm_error, TestVm};
+
+const COMPILE: &str = env!("CONTRACT_COMPILE");
+
+static EXPENSIVE_SHA256: Config = Config {
+    host_costs: HostCosts {
+        sha256: 100_000,
+        ..HostCosts::new()
+    },
+    ..Config::new()
+};
+
+#[test]
+fn invoke_succeeds() {
+    TestVm::new()
+        .deploy(COMPILE)
+        .execute("invoke")
+        .assert_ok()
+        .assert_event_count(0);
+}
+
+#[test]
+fn invoke_logs() {
+    TestVm::new()
+        .deploy(COMPILE)
+        .execute("invoke")
+        .assert_ok()
+        .assert_debug_contains("hello")
+        .assert_debug_contains("hash: ");
+}
+
+#[test]
+fn invoke_spends_gas_within_limit() {
+    let outcome = TestVm::new().deploy(COMPILE).execute("invoke");
+
+    outcome
+        .assert_ok()
+        .assert_gas_at_most(vm_test::DEFAULT_GAS_LIMIT);
+    assert!(outcome.gas_spent() > 0);
+}
+
+#[test]
+fn invoke_spends_the_same_gas_each_time() {
+    let contract = TestVm::new().deploy(COMPILE);
+
+    let gas = contract.execute("invoke").gas_spent();
+    contract.execute("invoke").assert_ok().assert_gas_spent(gas);
+}
+
+#[test]
+fn host_costs_come_from_the_config() {
+    let cheap = TestVm::new().deploy(COMPILE).execute("invoke");
+    let expensive = TestVm::new()
+        .with_config(&EXPENSIVE_SHA256)
+        .deploy(COMPILE)
+        .execute("invoke");
+
+    expensive.assert_ok();
+    assert!(expensive.gas_spent() > cheap.gas_spent() + 90_000);
+}
+
+#[test]
+fn invoke_runs_out_of_gas() {
+    let outcome = TestVm::new()
+        .with_gas_limit(10)
+        .deploy(COMPILE)
+        .execute("invoke");
+
+    assert_vm_error!(outcome, VMError::OutOfGas);
+}
+
+#[test]
+fn unknown_entrypoint_fails() {
+    let outcome = TestVm::new().deploy(COMPILE).execute("missing");
+
+    assert_vm_error!(outcome, VMError::WasmerExportError(_));
+    outcome.assert_debug(&[]);
+}
@@ -0,0 +1,240 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Global allocators for contracts, chosen with one of the cargo features:
+//!
+//! - `wee_alloc`: small code size, frees memory
+//! - `bump_alloc`: [`BumpAlloc`], smallest and fastest, but never frees, for
+//!   short-lived calls
+//! - `dlmalloc`: larger, but faster and less fragmenting than `wee_alloc`
+//!
+//! Without any of them the contract has to declare its own
+//! `#[global_allocator]`.
+
+#[cfg(any(
+    all(feature = "wee_alloc", feature = "bump_alloc"),
+    all(feature = "wee_alloc", feature = "dlmalloc"),
+    all(feature = "bump_alloc", feature = "dlmalloc"),
+))]
+compile_error!("only one of `wee_alloc`, `bump_alloc` and `dlmalloc` can be enabled");
+
+/// Size of a page of wasm memory, in bytes
+pub const WASM_PAGE_SIZE: usize = 64 * 1024;
+
+#[cfg(all(target_family = "wasm", feature = "wee_alloc"))]
+#[global_allocator]
+static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
+
+#[cfg(all(target_family = "wasm", feature = "dlmalloc"))]
+#[global_allocator]
+static ALLOC: dlmalloc::GlobalDlmalloc = dlmalloc::GlobalDlmalloc;
+
+#[cfg(all(target_family = "wasm", feature = "bump_alloc"))]
+#[global_allocator]
+static ALLOC: BumpAlloc = BumpAlloc::new();
+
+// `BumpAlloc` grows the memory with the wasm32 `memory.grow`
+#[cfg(all(target_family = "wasm", not(target_arch = "wasm32")))]
+compile_error!("`BumpAlloc` is only available on wasm32");
+
+#[cfg(target_family = "wasm")]
+pub use bump::BumpAlloc;
+
+#[cfg(any(target_family = "wasm", test))]
+mod bump {
+    use core::alloc::Layout;
+    use core::ptr;
+
+    use super::WASM_PAGE_SIZE;
+
+    #[cfg(target_family = "wasm")]
+    pub use self::global::BumpAlloc;
+
+    /// The free part of the last pages grown by.
+    struct Heap {
+        next: usize,
+        end: usize,
+    }
+
+    impl Heap {
+        const fn new() -> Self {
+            Heap { next: 0, end: 0 }
+        }
+
+        fn take(&mut self, layout: Layout) -> Option<*mut u8> {
+            let start = self.next.checked_add(layout.align() - 1)? & !(layout.align() - 1);
+            let end = start.checked_add(layout.size())?;
+            if end > self.end {
+                return None;
+            }
+
+            self.next = end;
+            Some(start as *mut u8)
+        }
+
+        /// Allocates `layout`, growing the memory with `grow` when the free
+        /// part is too small. Like `memory.grow`, `grow` takes a number of
+        /// pages and returns the previous number of pages, or `usize::MAX`
+        /// if the memory can't grow.
+        fn alloc(&mut self, layout: Layout, grow: impl FnOnce(usize) -> usize) -> *mut u8 {
+            if let Some(ptr) = self.take(layout) {
+                return ptr;
+            }
+
+            // enough for the allocation even if the new pages do not follow
+            // the current ones
+            let pages = match layout.size().checked_add(layout.align()) {
+                Some(len) => (len + WASM_PAGE_SIZE - 1) / WASM_PAGE_SIZE,
+                None => return ptr::null_mut(),
+            };
+            let previous = grow(pages);
+            if previous == usize::MAX {
+                return ptr::null_mut();
+            }
+
+            let start = previous * WASM_PAGE_SIZE;
+            if start != self.end {
+                self.next = start;
+            }
+            self.end = start + pages * WASM_PAGE_SIZE;
+
+            self.take(layout).unwrap_or(ptr::null_mut())
+        }
+    }
+
+    #[cfg(target_family = "wasm")]
+    mod global {
+        use core::alloc::{GlobalAlloc, Layout};
+        use core::arch::wasm32::memory_grow;
+        use core::cell::UnsafeCell;
+
+        use super::Heap;
+
+        /// Allocator handing out memory from pages it grows the wasm memory
+        /// by, without ever freeing it.
+        ///
+        /// Fits contracts whose calls allocate little, since the memory only
+        /// lives as long as the call.
+        pub struct BumpAlloc {
+            heap: UnsafeCell<Heap>,
+        }
+
+        // wasm contracts are single threaded
+        unsafe impl Sync for BumpAlloc {}
+
+        impl BumpAlloc {
+            /// Returns a new [`BumpAlloc`], which grows the memory on the
+            /// first allocation.
+            pub const fn new() -> Self {
+                BumpAlloc {
+                    heap: UnsafeCell::new(Heap::new()),
+                }
+            }
+        }
+
+        impl Default for BumpAlloc {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        unsafe impl GlobalAlloc for BumpAlloc {
+            unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+                (*self.heap.get()).alloc(layout, |pages| memory_grow(0, pages))
+            }
+
+            unsafe fn dealloc(&self, _: *mut u8, _: Layout) {}
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn layout(size: usize, align: usize) -> Layout {
+            Layout::from_size_align(size, align).unwrap()
+        }
+
+        #[test]
+        fn first_allocation_grows() {
+            let mut heap = Heap::new();
+            let mut grown = None;
+
+            let ptr = heap.alloc(layout(16, 8), |pages| {
+                grown = Some(pages);
+                2
+            });
+
+            assert_eq!(grown, Some(1));
+            assert_eq!(ptr as usize, 2 * WASM_PAGE_SIZE);
+        }
+
+        #[test]
+        fn allocations_are_aligned() {
+            let mut heap = Heap::new();
+            heap.alloc(layout(1, 1), |_| 1);
+
+            for align in [2, 4, 8, 16, 64, 4096] {
+                let ptr = heap.alloc(layout(3, align), |_| panic!("no need to grow"));
+                assert_eq!(ptr as usize % align, 0, "align {}", align);
+            }
+        }
+
+        #[test]
+        fn allocations_follow_each_other() {
+            let mut heap = Heap::new();
+
+            let a = heap.alloc(layout(5, 1), |_| 1) as usize;
+            let b = heap.alloc(layout(4, 4), |_| panic!("no need to grow")) as usize;
+            let c = heap.alloc(layout(1, 1), |_| panic!("no need to grow")) as usize;
+
+            assert_eq!(a, WASM_PAGE_SIZE);
+            assert_eq!(b, WASM_PAGE_SIZE + 8);
+            assert_eq!(c, WASM_PAGE_SIZE + 12);
+        }
+
+        #[test]
+        fn growth_covers_size_and_alignment() {
+            let mut heap = Heap::new();
+            let mut grown = None;
+
+            let ptr = heap.alloc(layout(WASM_PAGE_SIZE, 8), |pages| {
+                grown = Some(pages);
+                1
+            });
+
+            assert_eq!(grown, Some(2));
+            assert_eq!(ptr as usize, WASM_PAGE_SIZE);
+        }
+
+        #[test]
+        fn contiguous_growth_extends_the_free_part() {
+            let mut heap = Heap::new();
+            heap.alloc(layout(WASM_PAGE_SIZE - 8, 1), |_| 1);
+
+            // the new page follows the current one, so the allocation
+            // straddles both
+            let ptr = heap.alloc(layout(16, 8), |_| 2);
+            assert_eq!(ptr as usize, 2 * WASM_PAGE_SIZE - 8);
+        }
+
+        #[test]
+        fn disjoint_growth_starts_over() {
+            let mut heap = Heap::new();
+            heap.alloc(layout(WASM_PAGE_SIZE - 8, 1), |_| 1);
+
+            // something else grew the memory in between
+            let ptr = heap.alloc(layout(16, 8), |_| 5);
+            assert_eq!(ptr as usize, 5 * WASM_PAGE_SIZE);
+        }
+
+        #[test]
+        fn failed_growth_returns_null() {
+            let mut heap = Heap::new();
+            assert!(heap.alloc(layout(16, 8), |_| usize::MAX).is_null());
+        }
+    }
+}
@@ -0,0 +1,64 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use bytecheck::CheckBytes;
+use rkyv::{Archive, Deserialize, Serialize};
+
+/// Identifier of a deployed contract, the 32 byte blake2b hash of its
+/// bytecode
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Archive, Serialize, Deserialize,
+)]
+#[archive_attr(derive(CheckBytes))]
+#[repr(transparent)]
+pub struct ContractId(pub [u8; 32]);
+
+/// Failure of a call to another contract
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallError {
+    /// The callee ran out of the gas it was given
+    OutOfGas,
+    /// The callee aborted with the given error code
+    Abort(i32),
+    /// The callee panicked or trapped
+    Panic,
+    /// No contract is deployed under the given id
+    UnknownContract,
+    /// The callee doesn't export the called function
+    UnknownFunction,
+    /// The value returned doesn't match the expected type
+    InvalidReturn,
+    /// The value returned doesn't fit the return buffer of the caller
+    ReturnTooLarge,
+}
+
+impl CallError {
+    /// Code the host returns when the callee ran out of gas
+    pub const OUT_OF_GAS: i32 = -1;
+    /// Code the host returns when the callee aborted
+    pub const ABORT: i32 = -2;
+    /// Code the host returns when the callee panicked or trapped
+    pub const PANIC: i32 = -3;
+    /// Code the host returns for unknown contracts
+    pub const UNKNOWN_CONTRACT: i32 = -4;
+    /// Code the host returns for unknown functions
+    pub const UNKNOWN_FUNCTION: i32 = -5;
+    /// Code the host returns when the return value doesn't fit
+    pub const RETURN_TOO_LARGE: i32 = -6;
+
+    /// Maps a negative code returned by the host to an error. The abort code
+    /// is only used for [`CallError::ABORT`].
+    pub fn from_code(code: i32, abort_code: i32) -> Self {
+        match code {
+            Self::OUT_OF_GAS => CallError::OutOfGas,
+            Self::ABORT => CallError::Abort(abort_code),
+            Self::UNKNOWN_CONTRACT => CallError::UnknownContract,
+            Self::UNKNOWN_FUNCTION => CallError::UnknownFunction,
+            Self::RETURN_TOO_LARGE => CallError::ReturnTooLarge,
+            _ => CallError::Panic,
+        }
+    }
+}
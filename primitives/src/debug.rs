@@ -4,32 +4,234 @@
 //
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
-#[doc(hidden)]
+use core::fmt;
+
+use bytecheck::CheckBytes;
+use rkyv::{Archive, Deserialize, Serialize};
+
+/// Size of the buffer log messages are formatted into
 pub const BUFFER_SIZE: usize = 1024;
 
-#[cfg(target_family = "wasm")]
-/// Macro to format and send debug output to the host
+/// Appended to log messages truncated to [`BUFFER_SIZE`] bytes
+pub const TRUNCATION_MARKER: &str = "...";
+
+/// Level of a log message, passed to the host
+#[derive(
+    Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Archive, Serialize, Deserialize,
+)]
+#[archive_attr(derive(CheckBytes))]
+#[repr(i32)]
+pub enum LogLevel {
+    /// Very verbose information
+    Trace = 0,
+    /// Information useful when debugging
+    Debug = 1,
+    /// General information
+    Info = 2,
+    /// Potential problems
+    Warn = 3,
+    /// Errors
+    Error = 4,
+}
+
+impl LogLevel {
+    /// Returns the level passed to the host as `level`, if any.
+    pub fn from_i32(level: i32) -> Option<Self> {
+        match level {
+            0 => Some(LogLevel::Trace),
+            1 => Some(LogLevel::Debug),
+            2 => Some(LogLevel::Info),
+            3 => Some(LogLevel::Warn),
+            4 => Some(LogLevel::Error),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            LogLevel::Trace => "TRACE",
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Info => "INFO",
+            LogLevel::Warn => "WARN",
+            LogLevel::Error => "ERROR",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Writes into a buffer, dropping what does not fit and marking the output
+/// as truncated.
+#[cfg(any(target_family = "wasm", feature = "mock"))]
+struct TruncatingWriter<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+    truncated: bool,
+}
+
+#[cfg(any(target_family = "wasm", feature = "mock"))]
+impl<'a> TruncatingWriter<'a> {
+    fn new(buf: &'a mut [u8]) -> Self {
+        TruncatingWriter {
+            buf,
+            len: 0,
+            truncated: false,
+        }
+    }
+
+    /// Returns what was written, ending with [`TRUNCATION_MARKER`] if it did
+    /// not all fit.
+    fn finish(self) -> &'a str {
+        let mut len = self.len;
+        if self.truncated {
+            len = len.min(self.buf.len() - TRUNCATION_MARKER.len());
+            // back off to the start of a character
+            while len > 0 && self.buf[len] & 0xc0 == 0x80 {
+                len -= 1;
+            }
+            self.buf[len..][..TRUNCATION_MARKER.len()]
+                .copy_from_slice(TRUNCATION_MARKER.as_bytes());
+            len += TRUNCATION_MARKER.len();
+        }
+
+        core::str::from_utf8(&self.buf[..len]).unwrap_or_default()
+    }
+}
+
+#[cfg(any(target_family = "wasm", feature = "mock"))]
+impl<'a> fmt::Write for TruncatingWriter<'a> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let remainder = &mut self.buf[self.len..];
+        if s.len() > remainder.len() {
+            let fits = remainder.len();
+            remainder.copy_from_slice(&s.as_bytes()[..fits]);
+            self.len += fits;
+            self.truncated = true;
+            // stop formatting, the rest is dropped anyway
+            return Err(fmt::Error);
+        }
+
+        remainder[..s.len()].copy_from_slice(s.as_bytes());
+        self.len += s.len();
+        Ok(())
+    }
+}
+
+/// Formats a message and sends it to the host with the given level. Messages
+/// longer than [`BUFFER_SIZE`] bytes are truncated, ending with
+/// [`TRUNCATION_MARKER`].
+///
+/// Used by the [`log!`](crate::log) macros.
+#[cfg(any(target_family = "wasm", feature = "mock"))]
+pub fn log_fmt(level: LogLevel, args: fmt::Arguments) {
+    let mut buffer = [0u8; BUFFER_SIZE];
+    let mut writer = TruncatingWriter::new(&mut buffer);
+    let _ = fmt::Write::write_fmt(&mut writer, args);
+    let message = writer.finish();
+
+    unsafe { crate::hosted::external::log(level as i32, message.as_ptr(), message.len() as i32) }
+}
+
+#[cfg(any(target_family = "wasm", feature = "mock"))]
+/// Macro to format and send a log message to the host, with the given
+/// [`LogLevel`]
 #[macro_export]
-macro_rules! debug {
+macro_rules! log {
+    ($level:expr, $($tt:tt)*) => {
+        $crate::debug::log_fmt($level, format_args!($($tt)*))
+    };
+}
+
+#[cfg(not(any(target_family = "wasm", feature = "mock")))]
+#[macro_export]
+macro_rules! log {
+    ($level:expr, $($tt:tt)*) => {
+        ()
+    };
+}
+
+/// Macro to format and send a log message to the host, at the
+/// [`LogLevel::Trace`] level
+#[macro_export]
+macro_rules! trace {
     ($($tt:tt)*) => {
-	#[allow(unused)]
-        use core::fmt::Write as _;
-        let mut buffer = [0u8; $crate::debug::BUFFER_SIZE];
-        let len = {
-            let mut bw = $crate::bufwriter::BufWriter::new(&mut buffer);
-            write!(bw, $($tt)*).unwrap();
-            bw.ofs()
-        };
-	unsafe {
-            $crate::hosted::external::debug(&buffer[0], len as i32)
-	}
+        $crate::log!($crate::debug::LogLevel::Trace, $($tt)*)
     };
 }
 
-#[cfg(not(target_family = "wasm"))]
+/// Macro to format and send a log message to the host, at the
+/// [`LogLevel::Debug`] level
 #[macro_export]
 macro_rules! debug {
     ($($tt:tt)*) => {
-        ()
+        $crate::log!($crate::debug::LogLevel::Debug, $($tt)*)
+    };
+}
+
+/// Macro to format and send a log message to the host, at the
+/// [`LogLevel::Info`] level
+#[macro_export]
+macro_rules! info {
+    ($($tt:tt)*) => {
+        $crate::log!($crate::debug::LogLevel::Info, $($tt)*)
+    };
+}
+
+/// Macro to format and send a log message to the host, at the
+/// [`LogLevel::Warn`] level
+#[macro_export]
+macro_rules! warn {
+    ($($tt:tt)*) => {
+        $crate::log!($crate::debug::LogLevel::Warn, $($tt)*)
+    };
+}
+
+/// Macro to format and send a log message to the host, at the
+/// [`LogLevel::Error`] level
+#[macro_export]
+macro_rules! error {
+    ($($tt:tt)*) => {
+        $crate::log!($crate::debug::LogLevel::Error, $($tt)*)
     };
 }
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use core::fmt::Write;
+
+    fn truncate<'a>(buf: &'a mut [u8], s: &str) -> &'a str {
+        let mut writer = TruncatingWriter::new(buf);
+        let _ = writer.write_str(s);
+        writer.finish()
+    }
+
+    #[test]
+    fn exact_fit_is_not_truncated() {
+        let mut buf = [0u8; 8];
+        assert_eq!(truncate(&mut buf, "12345678"), "12345678");
+    }
+
+    #[test]
+    fn overflow_ends_with_marker() {
+        let mut buf = [0u8; 8];
+        assert_eq!(truncate(&mut buf, "123456789"), "12345...");
+    }
+
+    #[test]
+    fn overflow_over_several_writes() {
+        let mut buf = [0u8; 8];
+        let mut writer = TruncatingWriter::new(&mut buf);
+        assert!(writer.write_str("1234").is_ok());
+        assert!(writer.write_str("56789").is_err());
+        assert_eq!(writer.finish(), "12345...");
+    }
+
+    #[test]
+    fn multibyte_character_cut_at_boundary() {
+        // 'é' spans the bytes 4 and 5, the marker starts at 5
+        let mut buf = [0u8; 8];
+        assert_eq!(truncate(&mut buf, "1234é6789"), "1234...");
+    }
+}
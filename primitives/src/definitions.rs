@@ -38,6 +38,29 @@ pub trait Query: Archive {
     type Return;
 }
 
+pub trait Apply<T>
+where
+    T: Transaction,
+{
+    fn apply(&mut self, t: T, store: StoreContext) -> T::Return;
+}
+
+/// A call that changes the state of a contract, the counterpart of [`Query`].
+pub trait Transaction: Archive {
+    const NAME: &'static str;
+
+    type Return;
+}
+
+/// Fails to compile unless `T` can be passed to a contract. Used by the
+/// `Query` and `Transaction` derives.
+#[doc(hidden)]
+pub fn assert_archivable<T>()
+where
+    T: Archive + Serialize<StoreSerializer<OffsetLen>>,
+{
+}
+
 // TODO, use borrowed bytes here?
 #[derive(Debug, Default)]
 pub struct ReturnValue {
@@ -63,6 +86,7 @@ impl ReturnValue {
         }
     }
 
+    #[allow(clippy::type_complexity)]
     pub fn cast<'a, T>(
         &'a self,
     ) -> Result<
@@ -151,6 +175,41 @@ impl<'a> RawQuery<'a> {
     }
 }
 
+#[derive(Debug, Default)]
+pub struct RawTransaction<'a> {
+    data: Vec<u8>,
+    name: &'a str,
+}
+
+impl<'a> RawTransaction<'a> {
+    pub fn new<T>(t: T, store: &StoreRef<OffsetLen>) -> Self
+    where
+        T: Transaction + Serialize<StoreSerializer<OffsetLen>>,
+    {
+        let mut ser = store.serializer();
+        ser.serialize_value(&t).unwrap();
+        RawTransaction {
+            data: ser.spill_bytes(|bytes| Vec::from(bytes)),
+            name: T::NAME,
+        }
+    }
+
+    pub fn from<D: Into<Vec<u8>>>(data: D, name: &'a str) -> Self {
+        Self {
+            data: data.into(),
+            name,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        self.name
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data[..]
+    }
+}
+
 #[derive(Debug)]
 pub enum ArchiveError {
     ArchiveValidationError,
@@ -6,111 +6,158 @@
 
 extern crate alloc;
 
-use core::cell::UnsafeCell;
+use core::cell::RefCell;
 
 use alloc::boxed::Box;
 use alloc::vec::Vec;
 
-use microkelvin::{OffsetLen, Store, Token, TokenBuffer};
+use microkelvin::{OffsetLen, Store, StoreRef, Token, TokenBuffer};
+use rkyv::ser::Serializer;
 use rkyv::Fallible;
 
+use crate::StoreContext;
+
+#[cfg(not(feature = "mock"))]
 extern "C" {
-    fn _put(slice: &u8, len: u16) -> u64;
-    fn _get(offset: u64, len: u16, buf: &mut u8);
+    fn _put(slice: *const u8, len: u32) -> u64;
+    fn _get(offset: u64, len: u32, buf: *mut u8);
+}
+
+#[cfg(feature = "mock")]
+use crate::mock::external::{_get, _put};
+
+/// Code the contract aborts with when a blob exceeds the limits of the store
+pub const STORE_LIMIT_CODE: i32 = -2;
+
+/// Bits of the offset of an [`OffsetLen`] holding the high bits of the
+/// length, since its length field only has 16.
+const LEN_HIGH_SHIFT: u32 = 48;
+const OFFSET_MASK: u64 = (1 << LEN_HIGH_SHIFT) - 1;
+
+/// Packs a store offset and a 32 bit length into an [`OffsetLen`].
+fn encode(offset: u64, len: u32) -> OffsetLen {
+    if offset > OFFSET_MASK {
+        crate::env::abort(STORE_LIMIT_CODE, "store offset out of range");
+    }
+    let high = ((len >> 16) as u64) << LEN_HIGH_SHIFT;
+    OffsetLen::new(offset | high, len as u16)
+}
+
+/// Unpacks the store offset and 32 bit length of an [`OffsetLen`].
+fn decode(ident: &OffsetLen) -> (u64, u32) {
+    let high = (ident.offset() >> LEN_HIGH_SHIFT) as u32;
+    (
+        ident.offset() & OFFSET_MASK,
+        (high << 16) | ident.len() as u32,
+    )
 }
 
 fn abi_put(slice: &[u8]) -> OffsetLen {
-    assert!(slice.len() <= u16::MAX as usize);
-    let len = slice.len() as u16;
-    let ofs = unsafe { _put(&slice[0], len) };
+    if slice.len() > u32::MAX as usize {
+        crate::env::abort(STORE_LIMIT_CODE, "blob too large for the store");
+    }
+    let len = slice.len() as u32;
+    let ofs = unsafe { _put(slice.as_ptr(), len) };
 
-    OffsetLen::new(ofs, len)
+    encode(ofs, len)
 }
 
 fn abi_get(offset: u64, buf: &mut [u8]) {
-    let len = buf.len() as u16;
-    unsafe { _get(offset, len, &mut buf[0]) }
+    let len = buf.len() as u32;
+    unsafe { _get(offset, len, buf.as_mut_ptr()) }
 }
 
 const PAGE_SIZE: usize = 1024 * 64;
+
+/// A chunk of memory bytes are read or serialized into. Pages are boxed, so
+/// their bytes don't move when more pages are added.
 #[derive(Debug)]
 struct Page {
-    bytes: Box<[u8; PAGE_SIZE]>,
+    bytes: Box<[u8]>,
     written: usize,
 }
+
 impl Page {
     fn new() -> Self {
+        Self::with_size(PAGE_SIZE)
+    }
+
+    /// Creates a page of at least [`PAGE_SIZE`] bytes, larger for blobs that
+    /// don't fit a regular page.
+    fn with_size(size: usize) -> Self {
+        let size = core::cmp::max(size, PAGE_SIZE);
         Page {
-            bytes: Box::new([0u8; PAGE_SIZE]),
+            bytes: alloc::vec![0u8; size].into_boxed_slice(),
             written: 0,
         }
     }
-    fn unwritten_tail(&mut self) -> &mut [u8] {
-        &mut self.bytes[self.written..]
+
+    fn remaining(&self) -> usize {
+        self.bytes.len() - self.written
     }
 }
-struct AbiStoreInner {
-    data: *mut [u8],
-    written: usize,
-    token: Token,
-    pages: Vec<Page>,
-}
 
-pub struct AbiStore {
-    inner: UnsafeCell<AbiStoreInner>,
+/// Location of bytes read from the host
+#[derive(Debug, Clone, Copy)]
+struct Region {
+    page: usize,
+    start: usize,
+    len: usize,
 }
 
-impl Fallible for AbiStore {
-    type Error = core::convert::Infallible;
+struct AbiStoreInner {
+    token: Token,
+    /// Pages bytes read from the host are kept in. They are only appended
+    /// to, so any number of reads can be outstanding at once.
+    read_pages: Vec<Page>,
+    /// Page values are serialized into before being sent to the host
+    write_page: Page,
 }
 
 impl AbiStoreInner {
-    fn new(buf: &mut [u8]) -> Self {
+    fn new() -> Self {
         AbiStoreInner {
-            data: buf,
-            written: 0,
             token: Token::new(),
-            pages: Vec::new(),
+            read_pages: Vec::new(),
+            write_page: Page::new(),
         }
     }
 
-    fn unwritten_tail<'a>(&'a mut self) -> &'a mut [u8] {
-        let bytes = match self.pages.last_mut() {
-            Some(page) => page.unwritten_tail(),
-            None => {
-                let mut pages = Vec::with_capacity(1);
-                pages.push(Page::new());
+    /// Reserves `len` bytes in the last read page, starting a new page if
+    /// they don't fit.
+    fn reserve(&mut self, len: usize) -> Region {
+        let fits = matches!(self.read_pages.last(), Some(page) if page.remaining() >= len);
+        if !fits {
+            self.read_pages.push(Page::with_size(len));
+        }
+
+        let index = self.read_pages.len() - 1;
+        let page = &mut self.read_pages[index];
+        let start = page.written;
+        page.written += len;
 
-                self.pages = pages;
-                self.pages[0].unwritten_tail()
-            }
-        };
-        let extended: &'a mut [u8] = unsafe { core::mem::transmute(bytes) };
-        extended
+        Region {
+            page: index,
+            start,
+            len,
+        }
     }
 
-    fn extend(&mut self) {
-        self.pages.push(Page::new());
-        self.data = self.unwritten_tail();
-        self.written = 0;
+    fn region(&self, region: Region) -> &[u8] {
+        &self.read_pages[region.page].bytes[region.start..][..region.len]
     }
 
-    fn get(&mut self, ident: &OffsetLen) -> &[u8] {
-        let offset = ident.offset();
-        let len = ident.len() as usize;
-        let current_len = unsafe { &mut *self.data }.len();
-
-        if (self.written + len) > current_len {
-            self.extend();
-        }
-        let slice = unsafe { &mut *self.data };
-        let to_write = &mut slice[self.written..][..len as usize];
+    fn region_mut(&mut self, region: Region) -> &mut [u8] {
+        &mut self.read_pages[region.page].bytes[region.start..][..region.len]
+    }
 
-        self.written += len;
+    fn get(&mut self, ident: &OffsetLen) -> Region {
+        let (offset, len) = decode(ident);
+        let region = self.reserve(len as usize);
 
-        abi_get(offset, to_write);
+        abi_get(offset, self.region_mut(region));
 
-        to_write
+        region
     }
 
     fn return_token(&mut self, token: Token) {
@@ -118,44 +165,76 @@ impl AbiStoreInner {
     }
 
     fn request_buffer(&mut self) -> TokenBuffer {
-        let slice = unsafe { &mut *self.data };
-        let unwritten = &mut slice[self.written..];
         let token = self.token.take().expect("token error");
-        assert_eq!(self.written, 0, "Buffer must be requested when written is zero, if not, TokenBuffer will have to keep this offset to make extend work");
-        TokenBuffer::new(token, unwritten)
+        TokenBuffer::new(token, &mut self.write_page.bytes[..])
     }
 
     fn commit(&mut self, buffer: &mut TokenBuffer) -> OffsetLen {
-        let slice = buffer.written_bytes();
-        let len = slice.len() as usize;
-        let abi_put_ofslen = abi_put(slice);
-        let buf = buffer.as_mut() as *mut _ as *mut [u8];
-        buffer.remap(unsafe { &mut *buf }); // buffer.rewind();
-        assert!(len <= u16::MAX as usize);
-        self.written -= core::cmp::min(len, self.written);
-        abi_put_ofslen
+        let ident = abi_put(buffer.written_bytes());
+        buffer.remap(&mut self.write_page.bytes[..]);
+        ident
+    }
+
+    /// Replaces the write page with one twice as large, carrying over what
+    /// was written so far, so blobs larger than a page can be serialized.
+    fn extend(&mut self, buffer: &mut TokenBuffer) {
+        let written = buffer.written_bytes().len();
+        let page = Page::with_size(self.write_page.bytes.len() * 2);
+        let old_page = core::mem::replace(&mut self.write_page, page);
+
+        // remapping resets the buffer, so what was written is written again
+        buffer.remap(&mut self.write_page.bytes[..]);
+        buffer
+            .write(&old_page.bytes[..written])
+            .unwrap_or_else(|_| unreachable!("the new page is larger"));
     }
 }
 
+/// A [`Store`] over the `_put` and `_get` host functions
+pub struct AbiStore {
+    inner: RefCell<AbiStoreInner>,
+}
+
+impl Fallible for AbiStore {
+    type Error = core::convert::Infallible;
+}
+
 impl AbiStore {
-    pub fn new(buf: &mut [u8]) -> Self {
+    pub fn new() -> Self {
         AbiStore {
-            inner: UnsafeCell::new(AbiStoreInner::new(buf)),
+            inner: RefCell::new(AbiStoreInner::new()),
         }
     }
 }
 
+impl Default for AbiStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns a store backed by the host, for contracts to deserialize their
+/// arguments and serialize their return values with.
+pub fn guest_store() -> StoreContext {
+    StoreRef::new(AbiStore::new())
+}
+
 impl Store for AbiStore {
     type Identifier = OffsetLen;
 
     fn get(&self, ident: &OffsetLen) -> &[u8] {
-        let inner = unsafe { &mut *self.inner.get() };
-        inner.get(ident)
+        let mut inner = self.inner.borrow_mut();
+        let region = inner.get(ident);
+        let bytes: *const [u8] = inner.region(region);
+
+        // SAFETY: read pages are boxed and live as long as the store, and
+        // regions are never written to again once read, so the bytes stay
+        // valid after the cell is released.
+        unsafe { &*bytes }
     }
 
     fn request_buffer(&self) -> TokenBuffer {
-        let inner = unsafe { &mut *self.inner.get() };
-        inner.request_buffer()
+        self.inner.borrow_mut().request_buffer()
     }
 
     fn persist(&self) -> Result<(), ()> {
@@ -163,20 +242,15 @@ impl Store for AbiStore {
     }
 
     fn commit(&self, buffer: &mut TokenBuffer) -> Self::Identifier {
-        let inner = unsafe { &mut *self.inner.get() };
-        inner.commit(buffer)
+        self.inner.borrow_mut().commit(buffer)
     }
 
     fn extend(&self, buffer: &mut TokenBuffer) -> Result<(), ()> {
-        let inner = unsafe { &mut *self.inner.get() };
-        inner.extend();
-        let slice = unsafe { &mut *inner.data };
-        buffer.remap(slice);
+        self.inner.borrow_mut().extend(buffer);
         Ok(())
     }
 
     fn return_token(&self, token: Token) {
-        let inner = unsafe { &mut *self.inner.get() };
-        inner.return_token(token)
+        self.inner.borrow_mut().return_token(token)
     }
 }
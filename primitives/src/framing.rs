@@ -4,6 +4,8 @@
 //
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
+use core::cell::RefCell;
+
 use crate::StoreContext;
 use microkelvin::{OffsetLen, StoreSerializer};
 use rkyv::ser::Serializer;
@@ -33,12 +35,35 @@ where
     (state, arg)
 }
 
-pub fn q_return<R>(ret: &R, store: StoreContext) -> u32
+/// Value returned to the host in place of the encoded lengths when the
+/// return value of a call does not fit the scratch buffer.
+pub const RETURN_TOO_LARGE_CODE: u64 = u64::MAX;
+
+/// Serializes the return value of a call into the scratch buffer, returning
+/// the number of bytes written, or [`RETURN_TOO_LARGE_CODE`] if they do not
+/// fit.
+pub fn q_return<R>(ret: &R, store: StoreContext, scratch: impl AsMut<[u8]>) -> Result<u32, u64>
 where
     R: Archive + Serialize<StoreSerializer<OffsetLen>>,
 {
     let mut ser = store.serializer();
-    let buffer_len =
-        ser.serialize_value(ret).unwrap() + core::mem::size_of::<<R as Archive>::Archived>();
-    buffer_len as u32
+    ser.serialize_value(ret).unwrap();
+    // `spill_bytes` only takes `Fn` closures
+    let scratch = RefCell::new(scratch);
+    ser.spill_bytes(|bytes| {
+        let mut scratch = scratch.borrow_mut();
+        let scratch = scratch.as_mut();
+        if bytes.len() > scratch.len() {
+            return Err(RETURN_TOO_LARGE_CODE);
+        }
+        scratch[..bytes.len()].copy_from_slice(bytes);
+        Ok(bytes.len() as u32)
+    })
+}
+
+/// Encodes the lengths of the state and data written to the scratch buffer
+/// into the value returned to the host, the way
+/// [`ReturnValue::encode_lenghts`](crate::ReturnValue::encode_lenghts) does.
+pub fn encode_lengths(state_len: u32, data_len: u32) -> u64 {
+    ((state_len as u64 + data_len as u64) << 32) + state_len as u64
 }
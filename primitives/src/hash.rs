@@ -0,0 +1,42 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+/// Hash algorithms available through the streaming hash host functions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(i32)]
+pub enum HashAlgorithm {
+    /// SHA-256
+    Sha256 = 0,
+    /// SHA-512
+    Sha512 = 1,
+    /// Keccak-256, as used by Ethereum
+    Keccak256 = 2,
+    /// BLAKE2b with a 64 byte digest and no personalization
+    Blake2b = 3,
+}
+
+impl HashAlgorithm {
+    /// Returns the algorithm with the given identifier, if any
+    pub fn from_i32(id: i32) -> Option<Self> {
+        match id {
+            0 => Some(HashAlgorithm::Sha256),
+            1 => Some(HashAlgorithm::Sha512),
+            2 => Some(HashAlgorithm::Keccak256),
+            3 => Some(HashAlgorithm::Blake2b),
+            _ => None,
+        }
+    }
+
+    /// Returns the length of the digest produced by the algorithm
+    pub const fn digest_len(self) -> usize {
+        match self {
+            HashAlgorithm::Sha256 => 32,
+            HashAlgorithm::Sha512 => 64,
+            HashAlgorithm::Keccak256 => 32,
+            HashAlgorithm::Blake2b => 64,
+        }
+    }
+}
@@ -9,36 +9,131 @@
 
 extern crate alloc;
 
-pub use crate::{ArchiveError, Query, RawQuery, ReturnValue};
+pub use crate::{ArchiveError, Query, RawQuery, RawTransaction, ReturnValue, Transaction};
 
 use bytecheck::CheckBytes;
 use microkelvin::{OffsetLen, StoreRef, StoreSerializer};
 use rkyv::validation::validators::DefaultValidator;
 use rkyv::{Archive, Deserialize, Serialize};
 
-const BUFFER_SIZE_LIMIT: usize = 1024 * 16;
+/// Size of the buffer arguments and return values are passed through
+pub const BUFFER_SIZE_LIMIT: usize = 1024 * 16;
+
+/// Buffer the host writes the state and argument of a call into, and reads
+/// its return value from. The host looks it up by name.
+#[cfg(all(target_family = "wasm", not(feature = "host")))]
+#[no_mangle]
+pub static mut SCRATCH: [u8; BUFFER_SIZE_LIMIT] = [0u8; BUFFER_SIZE_LIMIT];
 
 // declare available host-calls
 pub mod external {
+    #[cfg(feature = "mock")]
+    pub use crate::mock::external::*;
+
+    #[cfg(not(feature = "mock"))]
+    #[cfg_attr(target_family = "wasm", link(wasm_import_module = "vm_v2"))]
     extern "C" {
-        pub fn debug(buffer: &u8, len: i32);
+        pub fn debug(buffer: *const u8, len: i32);
+
+        pub fn log(level: i32, msg: *const u8, msg_len: i32);
 
         pub fn gas_consumed() -> u64;
 
         pub fn gas_left() -> u64;
 
         pub fn sha256(input: &u8, input_len: u32, buffer: &mut u8);
+
+        pub fn abort(code: i32, msg: *const u8, msg_len: i32);
+
+        pub fn emit(topic: *const u8, topic_len: i32, data: *const u8, data_len: i32);
+
+        pub fn query(
+            target: &u8,
+            name: *const u8,
+            name_len: i32,
+            arg: *const u8,
+            arg_len: i32,
+            gas_limit: u64,
+            buffer: &mut u8,
+            buffer_len: i32,
+        ) -> i32;
+
+        pub fn transact(
+            target: &u8,
+            name: *const u8,
+            name_len: i32,
+            arg: *const u8,
+            arg_len: i32,
+            gas_limit: u64,
+            buffer: &mut u8,
+            buffer_len: i32,
+        ) -> i32;
+    }
+
+    #[cfg(not(feature = "mock"))]
+    #[cfg_attr(target_family = "wasm", link(wasm_import_module = "crypto"))]
+    extern "C" {
+        pub fn blake2b(
+            input: *const u8,
+            input_len: i32,
+            personal: *const u8,
+            personal_len: i32,
+            buffer: &mut u8,
+            buffer_len: i32,
+        );
+
+        pub fn keccak256(input: *const u8, input_len: i32, buffer: &mut u8);
+
+        pub fn sha512(input: *const u8, input_len: i32, buffer: &mut u8);
+
+        pub fn hash_init(algorithm: i32) -> i32;
+
+        pub fn hash_update(handle: i32, input: *const u8, input_len: i32);
+
+        pub fn hash_finalize(handle: i32, buffer: &mut u8, buffer_len: i32) -> i32;
+
+        pub fn verify_ed25519(pk: &u8, msg: *const u8, msg_len: i32, sig: &u8) -> i32;
+
+        pub fn verify_secp256k1(pk: *const u8, pk_len: i32, msg: &u8, sig: &u8) -> i32;
+
+        pub fn recover_secp256k1(msg: &u8, sig: &u8, buffer: &mut u8) -> i32;
+
+        pub fn verify_ed25519_batch(items: *const u8, items_len: i32) -> i32;
+
+        pub fn poseidon_hash(inputs: *const u8, inputs_len: i32, buffer: &mut u8) -> i32;
+
+        pub fn jubjub_add(a: &u8, b: &u8, buffer: &mut u8) -> i32;
+
+        pub fn jubjub_mul(point: &u8, scalar: &u8, buffer: &mut u8) -> i32;
+
+        pub fn bls_pairing_check(pairs: *const u8, pairs_len: i32) -> i32;
     }
 }
 
 pub mod env {
-    use super::external;
-    /// Write debug string
+    use super::{alloc, external, BUFFER_SIZE_LIMIT};
+    use crate::zk::{BlsScalar, JubJubAffine, JubJubScalar, PairingInput};
+    use crate::{CallError, ContractId, Ed25519BatchItem, HashAlgorithm, ReturnValue};
+    use alloc::vec::Vec;
+    use bytecheck::CheckBytes;
+    use microkelvin::{OffsetLen, StoreSerializer};
+    use rkyv::ser::serializers::AllocSerializer;
+    use rkyv::validation::validators::DefaultValidator;
+    use rkyv::{Archive, Deserialize, Serialize};
+
+    #[cfg(any(not(feature = "host"), feature = "mock"))]
+    use crate::{guest_store, Query, RawQuery, RawTransaction, StoreContext, Transaction};
+
+    /// Size of the scratch space used when serializing events
+    pub const EVENT_SCRATCH_SIZE: usize = 256;
+
+    /// Size of the scratch space used when serializing signature batches
+    pub const BATCH_SCRATCH_SIZE: usize = 1024;
+
+    /// Write debug string, of any length
     pub fn log(debug_string: impl AsRef<str>) {
-        let mut buffer = [0u8; 1024];
         let string = debug_string.as_ref();
-        buffer[..string.len()].copy_from_slice(string.as_bytes());
-        unsafe { external::debug(&buffer[0], string.len() as i32) }
+        unsafe { external::debug(string.as_ptr(), string.len() as i32) }
     }
 
     /// Return the amount of gas consumed until the point when the host call is
@@ -53,10 +148,278 @@ pub mod env {
         unsafe { external::gas_left() }
     }
 
+    /// Return the size of the memory of the contract in bytes, which the
+    /// host caps at `Config::max_memory_pages` pages of
+    /// [`WASM_PAGE_SIZE`](crate::WASM_PAGE_SIZE) bytes. Always zero outside
+    /// of wasm.
+    pub fn memory_usage() -> usize {
+        #[cfg(target_arch = "wasm32")]
+        {
+            core::arch::wasm32::memory_size(0) * crate::WASM_PAGE_SIZE
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            0
+        }
+    }
+
     pub fn sha256(bytes: &[u8]) -> [u8; 32] {
         let mut result = [0u8; 32];
         unsafe { external::sha256(&bytes[0], bytes.len() as _, &mut result[0]) };
 
         result
     }
+
+    /// Computes the blake2b hash of `bytes`, with a digest length of `N`
+    /// bytes and the given personalization. `N` must be between 1 and 64,
+    /// the personalization at most 16 bytes long.
+    pub fn blake2b<const N: usize>(bytes: &[u8], personal: &[u8]) -> [u8; N] {
+        let mut result = [0u8; N];
+        unsafe {
+            external::blake2b(
+                bytes.as_ptr(),
+                bytes.len() as i32,
+                personal.as_ptr(),
+                personal.len() as i32,
+                &mut result[0],
+                N as i32,
+            )
+        };
+
+        result
+    }
+
+    /// Computes the keccak256 hash of `bytes`, as used by Ethereum.
+    pub fn keccak256(bytes: &[u8]) -> [u8; 32] {
+        let mut result = [0u8; 32];
+        unsafe { external::keccak256(bytes.as_ptr(), bytes.len() as i32, &mut result[0]) };
+
+        result
+    }
+
+    /// Computes the sha512 hash of `bytes`.
+    pub fn sha512(bytes: &[u8]) -> [u8; 64] {
+        let mut result = [0u8; 64];
+        unsafe { external::sha512(bytes.as_ptr(), bytes.len() as i32, &mut result[0]) };
+
+        result
+    }
+
+    /// Verifies an Ed25519 signature of `msg`.
+    pub fn verify_ed25519(pk: &[u8; 32], msg: &[u8], sig: &[u8; 64]) -> bool {
+        unsafe { external::verify_ed25519(&pk[0], msg.as_ptr(), msg.len() as i32, &sig[0]) != 0 }
+    }
+
+    /// Verifies a secp256k1 ECDSA signature of the 32 byte `msg_hash`. The
+    /// public key can be either compressed or uncompressed.
+    pub fn verify_secp256k1(pk: &[u8], msg_hash: &[u8; 32], sig: &[u8; 64]) -> bool {
+        unsafe {
+            external::verify_secp256k1(pk.as_ptr(), pk.len() as i32, &msg_hash[0], &sig[0]) != 0
+        }
+    }
+
+    /// Verifies a batch of Ed25519 signatures in a single host call, with the
+    /// same rules as [`verify_ed25519`]. Returns the index of the first
+    /// invalid entry on failure.
+    pub fn verify_batch(items: &[Ed25519BatchItem]) -> Result<(), usize> {
+        let items = rkyv::to_bytes::<_, BATCH_SCRATCH_SIZE>(&items.to_vec()).unwrap();
+        match unsafe { external::verify_ed25519_batch(items.as_ptr(), items.len() as i32) } {
+            -1 => Ok(()),
+            index => Err(index as usize),
+        }
+    }
+
+    /// Recovers the uncompressed public key that signed the 32 byte
+    /// `msg_hash`, with the recovery id as the last byte of `sig`.
+    pub fn recover_secp256k1(msg_hash: &[u8; 32], sig: &[u8; 65]) -> Option<[u8; 65]> {
+        let mut result = [0u8; 65];
+        match unsafe { external::recover_secp256k1(&msg_hash[0], &sig[0], &mut result[0]) } {
+            0 => None,
+            _ => Some(result),
+        }
+    }
+
+    /// Computes the Poseidon sponge hash of BLS12-381 scalars. Returns `None`
+    /// if any input is not a canonical scalar.
+    pub fn poseidon_hash(inputs: &[BlsScalar]) -> Option<BlsScalar> {
+        let mut result = BlsScalar([0u8; 32]);
+        let ok = unsafe {
+            external::poseidon_hash(
+                inputs.as_ptr() as *const u8,
+                inputs.len() as i32,
+                &mut result.0[0],
+            )
+        };
+        (ok != 0).then(|| result)
+    }
+
+    /// Adds two JubJub points. Returns `None` if either is not a valid point.
+    pub fn jubjub_add(a: &JubJubAffine, b: &JubJubAffine) -> Option<JubJubAffine> {
+        let mut result = JubJubAffine([0u8; 32]);
+        let ok = unsafe { external::jubjub_add(&a.0[0], &b.0[0], &mut result.0[0]) };
+        (ok != 0).then(|| result)
+    }
+
+    /// Multiplies a JubJub point by a scalar. Returns `None` if either is not
+    /// valid.
+    pub fn jubjub_mul(point: &JubJubAffine, scalar: &JubJubScalar) -> Option<JubJubAffine> {
+        let mut result = JubJubAffine([0u8; 32]);
+        let ok = unsafe { external::jubjub_mul(&point.0[0], &scalar.0[0], &mut result.0[0]) };
+        (ok != 0).then(|| result)
+    }
+
+    /// Checks that the product of the pairings of the given points is the
+    /// identity. Returns `false` as well if any point is not valid.
+    pub fn bls_pairing_check(pairs: &[PairingInput]) -> bool {
+        unsafe { external::bls_pairing_check(pairs.as_ptr() as *const u8, pairs.len() as i32) != 0 }
+    }
+
+    /// A hash computed by the host over input passed in chunks, so it doesn't
+    /// have to be held in memory all at once.
+    ///
+    /// The host releases the hashers of a contract when its call returns.
+    #[derive(Debug)]
+    pub struct Hasher {
+        handle: i32,
+        algorithm: HashAlgorithm,
+    }
+
+    impl Hasher {
+        /// Starts a new hash with the given algorithm.
+        pub fn new(algorithm: HashAlgorithm) -> Self {
+            let handle = unsafe { external::hash_init(algorithm as i32) };
+            Hasher { handle, algorithm }
+        }
+
+        /// Feeds `bytes` into the hash.
+        pub fn update(&mut self, bytes: &[u8]) {
+            unsafe { external::hash_update(self.handle, bytes.as_ptr(), bytes.len() as i32) }
+        }
+
+        /// Finishes the hash, returning the digest.
+        pub fn finalize(self) -> Vec<u8> {
+            let digest_len = self.algorithm.digest_len();
+            let mut result = alloc::vec![0u8; digest_len];
+            let len =
+                unsafe { external::hash_finalize(self.handle, &mut result[0], digest_len as i32) };
+            result.truncate(len as usize);
+
+            result
+        }
+    }
+
+    /// Abort the execution of the contract with the given error code and
+    /// message, which the host reports as `VMError::ContractAbort`.
+    pub fn abort(code: i32, message: impl AsRef<str>) -> ! {
+        let message = message.as_ref();
+        unsafe {
+            external::abort(code, message.as_ptr(), message.len() as i32);
+            core::intrinsics::abort()
+        }
+    }
+
+    /// Emit an event under the given topic. Events are collected by the
+    /// host in the receipt of the execution, unless the contract fails.
+    pub fn emit<E>(topic: impl AsRef<str>, event: &E)
+    where
+        E: Serialize<AllocSerializer<EVENT_SCRATCH_SIZE>>,
+    {
+        let topic = topic.as_ref();
+        let data = rkyv::to_bytes::<_, EVENT_SCRATCH_SIZE>(event).unwrap();
+        unsafe {
+            external::emit(
+                topic.as_ptr(),
+                topic.len() as i32,
+                data.as_ptr(),
+                data.len() as i32,
+            )
+        }
+    }
+
+    type CallFn =
+        unsafe extern "C" fn(&u8, *const u8, i32, *const u8, i32, u64, &mut u8, i32) -> i32;
+
+    /// Calls a function of another contract through the host, returning the
+    /// raw return value.
+    fn call_contract(
+        call: CallFn,
+        target: &ContractId,
+        name: &str,
+        arg: &[u8],
+        gas_limit: u64,
+    ) -> Result<ReturnValue, CallError> {
+        let mut buffer = alloc::vec![0u8; BUFFER_SIZE_LIMIT];
+        let code = unsafe {
+            call(
+                &target.0[0],
+                name.as_ptr(),
+                name.len() as i32,
+                arg.as_ptr(),
+                arg.len() as i32,
+                gas_limit,
+                &mut buffer[0],
+                BUFFER_SIZE_LIMIT as i32,
+            )
+        };
+
+        if code < 0 {
+            let mut abort_code = [0u8; 4];
+            abort_code.copy_from_slice(&buffer[..4]);
+            return Err(CallError::from_code(code, i32::from_le_bytes(abort_code)));
+        }
+        Ok(ReturnValue::new(&buffer[..code as usize]))
+    }
+
+    /// Validates and deserializes the value returned by another contract.
+    #[cfg(any(not(feature = "host"), feature = "mock"))]
+    fn cast_return<R>(ret: &ReturnValue, store: &StoreContext) -> Result<R, CallError>
+    where
+        R: Archive,
+        R::Archived: for<'a> CheckBytes<DefaultValidator<'a>> + Deserialize<R, StoreContext>,
+    {
+        let archived = ret.cast::<R>().map_err(|_| CallError::InvalidReturn)?;
+        archived
+            .deserialize(&mut store.clone())
+            .map_err(|_| CallError::InvalidReturn)
+    }
+
+    /// Sends a query to another contract, giving it at most `gas_limit` of
+    /// the gas left, or the default share of it if `gas_limit` is zero.
+    #[cfg(any(not(feature = "host"), feature = "mock"))]
+    pub fn query<Q>(target: &ContractId, q: Q, gas_limit: u64) -> Result<Q::Return, CallError>
+    where
+        Q: Query + Serialize<StoreSerializer<OffsetLen>>,
+        Q::Return: Archive,
+        <Q::Return as Archive>::Archived:
+            for<'a> CheckBytes<DefaultValidator<'a>> + Deserialize<Q::Return, StoreContext>,
+    {
+        let store = guest_store();
+        let raw = RawQuery::new(q, &store);
+        let ret = call_contract(external::query, target, raw.name(), raw.data(), gas_limit)?;
+        cast_return(&ret, &store)
+    }
+
+    /// Applies a transaction to another contract, giving it at most
+    /// `gas_limit` of the gas left, or the default share of it if
+    /// `gas_limit` is zero. The new state of the callee is kept by the host,
+    /// unless the caller fails.
+    #[cfg(any(not(feature = "host"), feature = "mock"))]
+    pub fn transact<T>(target: &ContractId, t: T, gas_limit: u64) -> Result<T::Return, CallError>
+    where
+        T: Transaction + Serialize<StoreSerializer<OffsetLen>>,
+        T::Return: Archive,
+        <T::Return as Archive>::Archived:
+            for<'a> CheckBytes<DefaultValidator<'a>> + Deserialize<T::Return, StoreContext>,
+    {
+        let store = guest_store();
+        let raw = RawTransaction::new(t, &store);
+        let ret = call_contract(
+            external::transact,
+            target,
+            raw.name(),
+            raw.data(),
+            gas_limit,
+        )?;
+        cast_return(&ret, &store)
+    }
 }
@@ -8,24 +8,60 @@
 #![feature(core_intrinsics)]
 #![feature(alloc_error_handler)]
 #![feature(lang_items)]
+#![feature(panic_info_message)]
+
+// used by the code generated by `bindgen-macro`
+#[doc(hidden)]
+pub use rkyv;
 
 /// Store backend over FFI
-#[cfg(not(feature = "host"))]
+#[cfg(any(not(feature = "host"), feature = "abi_store", feature = "mock"))]
 mod ffi_store;
-#[cfg(not(feature = "host"))]
+#[cfg(any(not(feature = "host"), feature = "abi_store", feature = "mock"))]
 pub use ffi_store::*;
 
+pub mod allocator;
+pub use allocator::*;
+
 pub mod definitions;
 pub use definitions::*;
 
 mod hosted;
-pub use hosted::env;
+#[cfg(all(target_family = "wasm", not(feature = "host")))]
+pub use hosted::SCRATCH;
+pub use hosted::{env, BUFFER_SIZE_LIMIT};
 
 pub mod bufwriter;
 pub use bufwriter::*;
 
+pub mod call;
+pub use call::*;
+
 pub mod debug;
 pub use debug::*;
 
 pub mod framing;
 pub use framing::*;
+
+pub mod hash;
+pub use hash::*;
+
+#[cfg(feature = "mock")]
+pub mod mock;
+
+#[cfg(feature = "native")]
+pub mod native;
+
+pub mod oom;
+pub use oom::*;
+
+pub mod panic;
+pub use panic::*;
+
+pub mod signature;
+pub use signature::*;
+
+pub mod storage;
+pub use storage::{StorageMap, StorageValue, StorageVec};
+
+pub mod zk;
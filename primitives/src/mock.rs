@@ -0,0 +1,415 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Native stand-ins for the host functions, so contract logic can be unit
+//! tested with plain `cargo test`.
+//!
+//! The environment is kept per thread, and so per test. Contracts abort by
+//! panicking, which `#[should_panic]` tests can expect.
+
+extern crate alloc;
+extern crate std;
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+use crate::native::Hasher;
+use crate::{CallError, ContractId};
+
+/// An event emitted by the contract under test
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockEvent {
+    /// Topic of the event
+    pub topic: String,
+    /// Archived data of the event
+    pub data: Vec<u8>,
+}
+
+/// Handles the calls of the contract under test to other contracts, given
+/// the callee, the function name, the archived argument and whether the call
+/// is a transaction. Returns the archived return value or a negative
+/// [`CallError`] code.
+pub type CallHandler = Box<dyn FnMut(&ContractId, &str, &[u8], bool) -> Result<Vec<u8>, i32>>;
+
+struct MockEnv {
+    store: Vec<u8>,
+    logs: Vec<String>,
+    events: Vec<MockEvent>,
+    gas_consumed: u64,
+    gas_left: u64,
+    hashers: Vec<Option<Hasher>>,
+    call_handler: Option<CallHandler>,
+}
+
+impl MockEnv {
+    fn new() -> Self {
+        MockEnv {
+            store: Vec::new(),
+            logs: Vec::new(),
+            events: Vec::new(),
+            gas_consumed: 0,
+            gas_left: u64::MAX,
+            hashers: Vec::new(),
+            call_handler: None,
+        }
+    }
+}
+
+std::thread_local! {
+    static ENV: RefCell<MockEnv> = RefCell::new(MockEnv::new());
+}
+
+fn with_env<R>(f: impl FnOnce(&mut MockEnv) -> R) -> R {
+    ENV.with(|env| f(&mut env.borrow_mut()))
+}
+
+/// Resets the environment of the current thread.
+pub fn reset() {
+    with_env(|env| *env = MockEnv::new());
+}
+
+/// Sets the values `env::gas_consumed` and `env::gas_left` return.
+pub fn set_gas(consumed: u64, left: u64) {
+    with_env(|env| {
+        env.gas_consumed = consumed;
+        env.gas_left = left;
+    });
+}
+
+/// Returns the messages logged so far.
+pub fn logs() -> Vec<String> {
+    with_env(|env| env.logs.clone())
+}
+
+/// Takes the messages logged so far.
+pub fn take_logs() -> Vec<String> {
+    with_env(|env| core::mem::take(&mut env.logs))
+}
+
+/// Takes the events emitted so far.
+pub fn take_events() -> Vec<MockEvent> {
+    with_env(|env| core::mem::take(&mut env.events))
+}
+
+/// Sets the handler of calls to other contracts. Without one, calls fail
+/// with [`CallError::UnknownContract`].
+pub fn set_call_handler(
+    handler: impl FnMut(&ContractId, &str, &[u8], bool) -> Result<Vec<u8>, i32> + 'static,
+) {
+    with_env(|env| env.call_handler = Some(Box::new(handler)));
+}
+
+/// Native implementations of the functions in `hosted::external`, with the
+/// same signatures.
+#[allow(clippy::missing_safety_doc)]
+pub mod external {
+    use super::*;
+
+    use core::convert::TryInto;
+
+    use rkyv::{check_archived_root, AlignedVec, Deserialize, Infallible};
+
+    use crate::native::{self, PAIR_SIZE};
+    use crate::{Ed25519BatchItem, HashAlgorithm};
+
+    unsafe fn bytes<'a>(ptr: *const u8, len: impl TryInto<usize>) -> &'a [u8] {
+        let len = len.try_into().ok().expect("negative length");
+        if len == 0 {
+            return &[];
+        }
+        core::slice::from_raw_parts(ptr, len)
+    }
+
+    unsafe fn bytes_mut<'a>(ptr: *mut u8, len: impl TryInto<usize>) -> &'a mut [u8] {
+        let len = len.try_into().ok().expect("negative length");
+        if len == 0 {
+            return &mut [];
+        }
+        core::slice::from_raw_parts_mut(ptr, len)
+    }
+
+    unsafe fn array<const N: usize>(ptr: &u8) -> [u8; N] {
+        let mut array = [0u8; N];
+        array.copy_from_slice(bytes(ptr, N));
+        array
+    }
+
+    unsafe fn arrays<'a, const N: usize>(ptr: *const u8, len: i32) -> &'a [[u8; N]] {
+        let len: usize = len.try_into().expect("negative length");
+        if len == 0 {
+            return &[];
+        }
+        core::slice::from_raw_parts(ptr.cast(), len)
+    }
+
+    pub unsafe extern "C" fn debug(buffer: *const u8, len: i32) {
+        let message = String::from_utf8_lossy(bytes(buffer, len)).into_owned();
+        with_env(|env| env.logs.push(message));
+    }
+
+    pub unsafe extern "C" fn log(_level: i32, msg: *const u8, msg_len: i32) {
+        debug(msg, msg_len)
+    }
+
+    pub unsafe extern "C" fn gas_consumed() -> u64 {
+        with_env(|env| env.gas_consumed)
+    }
+
+    pub unsafe extern "C" fn gas_left() -> u64 {
+        with_env(|env| env.gas_left)
+    }
+
+    pub unsafe extern "C" fn sha256(input: &u8, input_len: u32, buffer: &mut u8) {
+        bytes_mut(buffer, 32).copy_from_slice(&native::sha256(bytes(input, input_len)));
+    }
+
+    pub unsafe extern "C" fn abort(code: i32, msg: *const u8, msg_len: i32) {
+        let message = String::from_utf8_lossy(bytes(msg, msg_len));
+        panic!("Contract aborted with code {}: {}", code, message);
+    }
+
+    pub unsafe extern "C" fn emit(
+        topic: *const u8,
+        topic_len: i32,
+        data: *const u8,
+        data_len: i32,
+    ) {
+        let event = MockEvent {
+            topic: String::from_utf8_lossy(bytes(topic, topic_len)).into_owned(),
+            data: bytes(data, data_len).to_vec(),
+        };
+        with_env(|env| env.events.push(event));
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn call(
+        target: &u8,
+        name: *const u8,
+        name_len: i32,
+        arg: *const u8,
+        arg_len: i32,
+        buffer: &mut u8,
+        buffer_len: i32,
+        transact: bool,
+    ) -> i32 {
+        let target = ContractId(array(target));
+        let name = String::from_utf8_lossy(bytes(name, name_len)).into_owned();
+        let arg = bytes(arg, arg_len).to_vec();
+
+        // the handler is taken out, so it can use the environment itself
+        let handler = with_env(|env| env.call_handler.take());
+        let (handler, result) = match handler {
+            Some(mut handler) => {
+                let result = handler(&target, &name, &arg, transact);
+                (Some(handler), result)
+            }
+            None => (None, Err(CallError::UNKNOWN_CONTRACT)),
+        };
+        with_env(|env| env.call_handler = handler);
+
+        let buffer = bytes_mut(buffer, buffer_len);
+        match result {
+            Ok(ret) if ret.len() <= buffer.len() => {
+                buffer[..ret.len()].copy_from_slice(&ret);
+                ret.len() as i32
+            }
+            Ok(_) => CallError::RETURN_TOO_LARGE,
+            Err(code) => code,
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub unsafe extern "C" fn query(
+        target: &u8,
+        name: *const u8,
+        name_len: i32,
+        arg: *const u8,
+        arg_len: i32,
+        _gas_limit: u64,
+        buffer: &mut u8,
+        buffer_len: i32,
+    ) -> i32 {
+        call(
+            target, name, name_len, arg, arg_len, buffer, buffer_len, false,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub unsafe extern "C" fn transact(
+        target: &u8,
+        name: *const u8,
+        name_len: i32,
+        arg: *const u8,
+        arg_len: i32,
+        _gas_limit: u64,
+        buffer: &mut u8,
+        buffer_len: i32,
+    ) -> i32 {
+        call(
+            target, name, name_len, arg, arg_len, buffer, buffer_len, true,
+        )
+    }
+
+    pub unsafe extern "C" fn blake2b(
+        input: *const u8,
+        input_len: i32,
+        personal: *const u8,
+        personal_len: i32,
+        buffer: &mut u8,
+        buffer_len: i32,
+    ) {
+        assert!(
+            (1..=64).contains(&buffer_len),
+            "invalid blake2b digest length"
+        );
+        assert!(personal_len <= 16, "blake2b personalization too long");
+
+        let digest = native::blake2b(
+            bytes(input, input_len),
+            bytes(personal, personal_len),
+            buffer_len as usize,
+        );
+        bytes_mut(buffer, buffer_len).copy_from_slice(digest.as_bytes());
+    }
+
+    pub unsafe extern "C" fn keccak256(input: *const u8, input_len: i32, buffer: &mut u8) {
+        bytes_mut(buffer, 32).copy_from_slice(&native::keccak256(bytes(input, input_len)));
+    }
+
+    pub unsafe extern "C" fn sha512(input: *const u8, input_len: i32, buffer: &mut u8) {
+        bytes_mut(buffer, 64).copy_from_slice(&native::sha512(bytes(input, input_len)));
+    }
+
+    pub unsafe extern "C" fn hash_init(algorithm: i32) -> i32 {
+        let algorithm = HashAlgorithm::from_i32(algorithm).expect("unknown hash algorithm");
+        with_env(|env| {
+            env.hashers.push(Some(Hasher::new(algorithm)));
+            (env.hashers.len() - 1) as i32
+        })
+    }
+
+    pub unsafe extern "C" fn hash_update(handle: i32, input: *const u8, input_len: i32) {
+        let input = bytes(input, input_len);
+        with_env(|env| {
+            env.hashers[handle as usize]
+                .as_mut()
+                .expect("invalid handle")
+                .update(input)
+        });
+    }
+
+    pub unsafe extern "C" fn hash_finalize(handle: i32, buffer: &mut u8, buffer_len: i32) -> i32 {
+        let hasher = with_env(|env| env.hashers[handle as usize].take().expect("invalid handle"));
+        let digest = hasher.finalize();
+
+        assert!(
+            buffer_len as usize >= digest.len(),
+            "digest buffer too small"
+        );
+        bytes_mut(buffer, digest.len()).copy_from_slice(&digest);
+        digest.len() as i32
+    }
+
+    pub unsafe extern "C" fn verify_ed25519(
+        pk: &u8,
+        msg: *const u8,
+        msg_len: i32,
+        sig: &u8,
+    ) -> i32 {
+        native::verify_ed25519(&array(pk), bytes(msg, msg_len), &array(sig)) as i32
+    }
+
+    pub unsafe extern "C" fn verify_secp256k1(
+        pk: *const u8,
+        pk_len: i32,
+        msg: &u8,
+        sig: &u8,
+    ) -> i32 {
+        native::verify_secp256k1(bytes(pk, pk_len), &array(msg), &array(sig)) as i32
+    }
+
+    pub unsafe extern "C" fn recover_secp256k1(msg: &u8, sig: &u8, buffer: &mut u8) -> i32 {
+        match native::recover_secp256k1(&array(msg), &array(sig)) {
+            Some(pk) => {
+                bytes_mut(buffer, 65).copy_from_slice(&pk);
+                1
+            }
+            None => 0,
+        }
+    }
+
+    pub unsafe extern "C" fn verify_ed25519_batch(items: *const u8, items_len: i32) -> i32 {
+        let mut aligned = AlignedVec::new();
+        aligned.extend_from_slice(bytes(items, items_len));
+
+        let items: Vec<Ed25519BatchItem> = check_archived_root::<Vec<Ed25519BatchItem>>(&aligned)
+            .expect("invalid signature batch")
+            .deserialize(&mut Infallible)
+            .expect("infallible");
+
+        if native::verify_ed25519_batch(&items) {
+            return -1;
+        }
+        items
+            .iter()
+            .position(|item| !native::verify_ed25519(&item.pk, &item.msg, &item.sig))
+            .map_or(-1, |i| i as i32)
+    }
+
+    pub unsafe extern "C" fn poseidon_hash(
+        inputs: *const u8,
+        inputs_len: i32,
+        buffer: &mut u8,
+    ) -> i32 {
+        match native::poseidon_hash(arrays(inputs, inputs_len)) {
+            Some(hash) => {
+                bytes_mut(buffer, 32).copy_from_slice(&hash);
+                1
+            }
+            None => 0,
+        }
+    }
+
+    pub unsafe extern "C" fn jubjub_add(a: &u8, b: &u8, buffer: &mut u8) -> i32 {
+        match native::jubjub_add(&array(a), &array(b)) {
+            Some(sum) => {
+                bytes_mut(buffer, 32).copy_from_slice(&sum);
+                1
+            }
+            None => 0,
+        }
+    }
+
+    pub unsafe extern "C" fn jubjub_mul(point: &u8, scalar: &u8, buffer: &mut u8) -> i32 {
+        match native::jubjub_mul(&array(point), &array(scalar)) {
+            Some(product) => {
+                bytes_mut(buffer, 32).copy_from_slice(&product);
+                1
+            }
+            None => 0,
+        }
+    }
+
+    pub unsafe extern "C" fn bls_pairing_check(pairs: *const u8, pairs_len: i32) -> i32 {
+        native::bls_pairing_check(arrays::<PAIR_SIZE>(pairs, pairs_len)) as i32
+    }
+
+    pub unsafe extern "C" fn _put(slice: *const u8, len: u32) -> u64 {
+        let slice = bytes(slice, len);
+        with_env(|env| {
+            let offset = env.store.len() as u64;
+            env.store.extend_from_slice(slice);
+            offset
+        })
+    }
+
+    pub unsafe extern "C" fn _get(offset: u64, len: u32, buf: *mut u8) {
+        let buf = bytes_mut(buf, len);
+        with_env(|env| buf.copy_from_slice(&env.store[offset as usize..][..len as usize]));
+    }
+}
@@ -0,0 +1,238 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Native implementations of the hashing, signature and zero-knowledge
+//! friendly host functions, shared by the VM and by the `mock` feature so
+//! both compute the same results.
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use dusk_bls12_381::{multi_miller_loop, BlsScalar, G1Affine, G2Affine, G2Prepared, Gt};
+use dusk_bytes::Serializable;
+use dusk_jubjub::{JubJubAffine, JubJubExtended, JubJubScalar};
+use sha2::Digest;
+
+use crate::{Ed25519BatchItem, HashAlgorithm};
+
+/// Size of a compressed G1 point followed by a compressed G2 point, as laid
+/// out by [`PairingInput`](crate::zk::PairingInput).
+pub const PAIR_SIZE: usize = G1Affine::SIZE + G2Affine::SIZE;
+
+/// Hashes `input` with sha256.
+pub fn sha256(input: &[u8]) -> [u8; 32] {
+    sha2::Sha256::digest(input).into()
+}
+
+/// Hashes `input` with sha512.
+pub fn sha512(input: &[u8]) -> [u8; 64] {
+    let mut out = [0u8; 64];
+    out.copy_from_slice(&sha2::Sha512::digest(input));
+    out
+}
+
+/// Hashes `input` with keccak256, as Ethereum does.
+pub fn keccak256(input: &[u8]) -> [u8; 32] {
+    sha3::Keccak256::digest(input).into()
+}
+
+/// Hashes `input` with blake2b into a digest of `len` bytes, with the given
+/// personalization.
+pub fn blake2b(input: &[u8], personal: &[u8], len: usize) -> blake2b_simd::Hash {
+    blake2b_simd::Params::new()
+        .hash_length(len)
+        .personal(personal)
+        .to_state()
+        .update(input)
+        .finalize()
+}
+
+/// The state of a hash computed over several `hash_update` calls.
+pub enum Hasher {
+    Sha256(sha2::Sha256),
+    Sha512(sha2::Sha512),
+    Keccak256(sha3::Keccak256),
+    Blake2b(blake2b_simd::State),
+}
+
+impl Hasher {
+    /// Starts a hash with the given algorithm.
+    pub fn new(algorithm: HashAlgorithm) -> Self {
+        match algorithm {
+            HashAlgorithm::Sha256 => Hasher::Sha256(sha2::Sha256::new()),
+            HashAlgorithm::Sha512 => Hasher::Sha512(sha2::Sha512::new()),
+            HashAlgorithm::Keccak256 => Hasher::Keccak256(sha3::Keccak256::new()),
+            HashAlgorithm::Blake2b => Hasher::Blake2b(blake2b_simd::State::new()),
+        }
+    }
+
+    /// Feeds `bytes` to the hash.
+    pub fn update(&mut self, bytes: &[u8]) {
+        match self {
+            Hasher::Sha256(hasher) => hasher.update(bytes),
+            Hasher::Sha512(hasher) => hasher.update(bytes),
+            Hasher::Keccak256(hasher) => hasher.update(bytes),
+            Hasher::Blake2b(state) => {
+                state.update(bytes);
+            }
+        }
+    }
+
+    /// Returns the digest of the bytes fed so far.
+    pub fn finalize(self) -> Vec<u8> {
+        match self {
+            Hasher::Sha256(hasher) => hasher.finalize().to_vec(),
+            Hasher::Sha512(hasher) => hasher.finalize().to_vec(),
+            Hasher::Keccak256(hasher) => hasher.finalize().to_vec(),
+            Hasher::Blake2b(mut state) => state.finalize().as_bytes().to_vec(),
+        }
+    }
+}
+
+/// Verifies an Ed25519 signature, rejecting malformed keys and signatures.
+pub fn verify_ed25519(pk: &[u8; 32], msg: &[u8], sig: &[u8; 64]) -> bool {
+    let pk = match ed25519_dalek::PublicKey::from_bytes(pk) {
+        Ok(pk) => pk,
+        Err(_) => return false,
+    };
+    let sig = match ed25519_dalek::Signature::try_from(&sig[..]) {
+        Ok(sig) => sig,
+        Err(_) => return false,
+    };
+    pk.verify_strict(msg, &sig).is_ok()
+}
+
+/// Verifies a batch of Ed25519 signatures at once, returning `true` only if
+/// all of them pass [`verify_ed25519`].
+///
+/// `ed25519_dalek::verify_batch` accepts the keys and commitments of small
+/// order that `verify_strict` rejects, so a batch holding any of them fails.
+pub fn verify_ed25519_batch(items: &[Ed25519BatchItem]) -> bool {
+    let mut pks = Vec::with_capacity(items.len());
+    let mut sigs = Vec::with_capacity(items.len());
+
+    for item in items {
+        let mut r = [0u8; 32];
+        r.copy_from_slice(&item.sig[..32]);
+        if !is_strict_point(&item.pk) || !is_strict_point(&r) {
+            return false;
+        }
+
+        match (
+            ed25519_dalek::PublicKey::from_bytes(&item.pk),
+            ed25519_dalek::Signature::try_from(&item.sig[..]),
+        ) {
+            (Ok(pk), Ok(sig)) => {
+                pks.push(pk);
+                sigs.push(sig);
+            }
+            _ => return false,
+        }
+    }
+
+    let msgs: Vec<&[u8]> = items.iter().map(|item| &item.msg[..]).collect();
+    ed25519_dalek::verify_batch(&msgs, &sigs, &pks).is_ok()
+}
+
+/// Returns `true` if `point` is a valid Edwards point not of small order.
+fn is_strict_point(point: &[u8; 32]) -> bool {
+    match CompressedEdwardsY(*point).decompress() {
+        Some(point) => !point.is_small_order(),
+        None => false,
+    }
+}
+
+/// Verifies a secp256k1 signature of a 32 byte message hash against a
+/// compressed or uncompressed public key, rejecting malformed keys and
+/// signatures.
+pub fn verify_secp256k1(pk: &[u8], msg: &[u8; 32], sig: &[u8; 64]) -> bool {
+    let pk = match libsecp256k1::PublicKey::parse_slice(pk, None) {
+        Ok(pk) => pk,
+        Err(_) => return false,
+    };
+    let sig = match libsecp256k1::Signature::parse_standard(sig) {
+        Ok(sig) => sig,
+        Err(_) => return false,
+    };
+
+    libsecp256k1::verify(&libsecp256k1::Message::parse(msg), &sig, &pk)
+}
+
+/// Recovers the uncompressed public key from a 65 byte signature of a 32
+/// byte message hash, or `None` if the signature is invalid. The last byte
+/// of the signature is the recovery id - either `0`/`1` or Ethereum's
+/// `27`/`28`.
+pub fn recover_secp256k1(msg: &[u8; 32], sig: &[u8; 65]) -> Option<[u8; 65]> {
+    let mut rs = [0u8; 64];
+    rs.copy_from_slice(&sig[..64]);
+    let v = match sig[64] {
+        v @ 27..=28 => v - 27,
+        v => v,
+    };
+
+    let msg = libsecp256k1::Message::parse(msg);
+    let sig = libsecp256k1::Signature::parse_standard(&rs).ok()?;
+    let recovery_id = libsecp256k1::RecoveryId::parse(v).ok()?;
+
+    libsecp256k1::recover(&msg, &sig, &recovery_id)
+        .ok()
+        .map(|pk| pk.serialize())
+}
+
+/// Hashes the given scalars with Poseidon, or returns `None` if any of them
+/// is not canonical.
+pub fn poseidon_hash(inputs: &[[u8; 32]]) -> Option<[u8; 32]> {
+    let scalars = inputs
+        .iter()
+        .map(|bytes| BlsScalar::from_bytes(bytes).ok())
+        .collect::<Option<Vec<_>>>()?;
+
+    Some(dusk_poseidon::sponge::hash(&scalars).to_bytes())
+}
+
+/// Adds two compressed JubJub points, or returns `None` if either is not
+/// valid.
+pub fn jubjub_add(a: &[u8; 32], b: &[u8; 32]) -> Option<[u8; 32]> {
+    let a = JubJubAffine::from_bytes(a).ok()?;
+    let b = JubJubAffine::from_bytes(b).ok()?;
+
+    let sum = JubJubAffine::from(JubJubExtended::from(a) + JubJubExtended::from(b));
+    Some(sum.to_bytes())
+}
+
+/// Multiplies a compressed JubJub point by a scalar, or returns `None` if
+/// either is not valid.
+pub fn jubjub_mul(point: &[u8; 32], scalar: &[u8; 32]) -> Option<[u8; 32]> {
+    let point = JubJubAffine::from_bytes(point).ok()?;
+    let scalar = JubJubScalar::from_bytes(scalar).ok()?;
+
+    let product = JubJubAffine::from(JubJubExtended::from(point) * scalar);
+    Some(product.to_bytes())
+}
+
+/// Checks that the product of the pairings of the G1/G2 pairs is the
+/// identity, returning `false` if it isn't or any of the points is not
+/// valid.
+pub fn bls_pairing_check(pairs: &[[u8; PAIR_SIZE]]) -> bool {
+    let mut points = Vec::with_capacity(pairs.len());
+    for pair in pairs {
+        let mut g1 = [0u8; G1Affine::SIZE];
+        let mut g2 = [0u8; G2Affine::SIZE];
+        g1.copy_from_slice(&pair[..G1Affine::SIZE]);
+        g2.copy_from_slice(&pair[G1Affine::SIZE..]);
+
+        match (G1Affine::from_bytes(&g1), G2Affine::from_bytes(&g2)) {
+            (Ok(g1), Ok(g2)) => points.push((g1, G2Prepared::from(g2))),
+            _ => return false,
+        }
+    }
+
+    let terms: Vec<(&G1Affine, &G2Prepared)> = points.iter().map(|(g1, g2)| (g1, g2)).collect();
+    multi_miller_loop(&terms).final_exponentiation() == Gt::identity()
+}
@@ -0,0 +1,42 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use core::alloc::Layout;
+use core::fmt::Write;
+
+use crate::bufwriter::BufWriter;
+use crate::env;
+
+/// Code the contract aborts with when it runs out of memory
+pub const OOM_CODE: i32 = -3;
+
+/// Aborts the contract with [`OOM_CODE`] and a message giving the size and
+/// alignment of the allocation that failed. Does not allocate.
+///
+/// `no_std` contracts report their allocation failures through it by
+/// enabling the `oom_handler` feature.
+pub fn report_oom(layout: Layout) -> ! {
+    let mut buffer = [0u8; 128];
+    let len = {
+        let mut bw = BufWriter::new(&mut buffer);
+        let _ = write!(
+            bw,
+            "out of memory allocating {} bytes aligned to {}",
+            layout.size(),
+            layout.align()
+        );
+        bw.ofs()
+    };
+
+    let message = core::str::from_utf8(&buffer[..len]).unwrap_or_default();
+    env::abort(OOM_CODE, message)
+}
+
+#[cfg(all(target_family = "wasm", feature = "oom_handler"))]
+#[alloc_error_handler]
+fn oom(layout: Layout) -> ! {
+    report_oom(layout)
+}
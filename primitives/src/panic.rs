@@ -0,0 +1,52 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use core::fmt::Write;
+use core::panic::PanicInfo;
+
+use crate::bufwriter::BufWriter;
+use crate::debug::BUFFER_SIZE;
+use crate::env;
+
+/// Code the contract aborts with when it panics
+pub const PANIC_CODE: i32 = -1;
+
+/// Aborts the contract with [`PANIC_CODE`] and a message of the form
+/// `file:line:column: message`, truncated to [`BUFFER_SIZE`] bytes.
+///
+/// Contracts linking `std` can report their panics with
+/// `std::panic::set_hook(Box::new(|info| primitives::report_panic(info)))`,
+/// `no_std` contracts by enabling the `panic_handler` feature.
+pub fn report_panic(info: &PanicInfo) -> ! {
+    let mut buffer = [0u8; BUFFER_SIZE];
+    let len = {
+        let mut bw = BufWriter::new(&mut buffer);
+        if let Some(location) = info.location() {
+            let _ = write!(
+                bw,
+                "{}:{}:{}: ",
+                location.file(),
+                location.line(),
+                location.column()
+            );
+        }
+        if let Some(message) = info.message() {
+            let _ = write!(bw, "{}", message);
+        } else if let Some(message) = info.payload().downcast_ref::<&str>() {
+            let _ = write!(bw, "{}", message);
+        }
+        bw.ofs()
+    };
+
+    let message = core::str::from_utf8(&buffer[..len]).unwrap_or_default();
+    env::abort(PANIC_CODE, message)
+}
+
+#[cfg(all(target_family = "wasm", feature = "panic_handler"))]
+#[panic_handler]
+fn panic(info: &PanicInfo) -> ! {
+    report_panic(info)
+}
@@ -0,0 +1,36 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use bytecheck::CheckBytes;
+use rkyv::{Archive, Deserialize, Serialize};
+
+/// An Ed25519 public key, message and signature to be verified as part of a
+/// batch
+#[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize)]
+#[archive_attr(derive(CheckBytes))]
+pub struct Ed25519BatchItem {
+    /// The public key of the signer
+    pub pk: [u8; 32],
+    /// The signed message
+    pub msg: Vec<u8>,
+    /// The signature
+    pub sig: [u8; 64],
+}
+
+impl Ed25519BatchItem {
+    /// Creates a new batch item
+    pub fn new(pk: [u8; 32], msg: impl Into<Vec<u8>>, sig: [u8; 64]) -> Self {
+        Ed25519BatchItem {
+            pk,
+            msg: msg.into(),
+            sig,
+        }
+    }
+}
@@ -0,0 +1,380 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Persistent collections for contract state.
+//!
+//! The collections archive as a reference into the store, so deserializing
+//! the state of a contract doesn't load them. Their contents are loaded
+//! through the store on first access, and written back on
+//! [`commit`](StorageValue::commit) or when the state is first serialized
+//! after a change.
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::borrow::BorrowMut;
+use core::cell::Cell;
+use core::fmt;
+use core::hash::Hash;
+
+use bytecheck::CheckBytes;
+use dusk_hamt::{Hamt, Lookup};
+use microkelvin::{ArchivedCompound, Ident, MaybeArchived, OffsetLen, StoreSerializer};
+use rkyv::validation::validators::DefaultValidator;
+use rkyv::{from_archived, to_archived, Archive, Archived, Deserialize, Fallible, Serialize};
+
+use crate::StoreContext;
+
+/// The archived form of the collections, a reference into the store
+#[derive(Debug, CheckBytes)]
+#[repr(C)]
+pub struct ArchivedStorageRef {
+    offset: Archived<u64>,
+    len: Archived<u16>,
+}
+
+impl ArchivedStorageRef {
+    fn ident(&self) -> OffsetLen {
+        OffsetLen::new(from_archived!(self.offset), from_archived!(self.len))
+    }
+}
+
+fn put<T>(store: &StoreContext, value: &T) -> OffsetLen
+where
+    T: Serialize<StoreSerializer<OffsetLen>>,
+{
+    *store.store(value).ident().erase()
+}
+
+fn get<T>(store: &StoreContext, ident: OffsetLen) -> T
+where
+    T: Archive,
+    T::Archived: Deserialize<T, StoreContext> + for<'a> CheckBytes<DefaultValidator<'a>>,
+{
+    deserialize(store.get::<T>(&Ident::new(ident)), store)
+}
+
+fn deserialize<T>(archived: &T::Archived, store: &StoreContext) -> T
+where
+    T: Archive,
+    T::Archived: Deserialize<T, StoreContext>,
+{
+    // the store is an infallible deserializer
+    match archived.deserialize(&mut store.clone()) {
+        Ok(value) => value,
+        Err(infallible) => match infallible {},
+    }
+}
+
+/// A single value kept in the store
+pub struct StorageValue<T> {
+    store: StoreContext,
+    // serializing writes a changed value, through a shared reference
+    ident: Cell<Option<OffsetLen>>,
+    value: Option<T>,
+    dirty: Cell<bool>,
+}
+
+impl<T: fmt::Debug> fmt::Debug for StorageValue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("StorageValue")
+            .field("ident", &self.ident.get())
+            .field("value", &self.value)
+            .finish()
+    }
+}
+
+impl<T> StorageValue<T>
+where
+    T: Archive + Serialize<StoreSerializer<OffsetLen>>,
+    T::Archived: Deserialize<T, StoreContext> + for<'a> CheckBytes<DefaultValidator<'a>>,
+{
+    /// Creates a new value, written to the store on commit.
+    pub fn new(store: &StoreContext, value: T) -> Self {
+        StorageValue {
+            store: store.clone(),
+            ident: Cell::new(None),
+            value: Some(value),
+            dirty: Cell::new(true),
+        }
+    }
+
+    fn load(&mut self) {
+        if self.value.is_none() {
+            let ident = self
+                .ident
+                .get()
+                .expect("either the value or its ident is set");
+            self.value = Some(get(&self.store, ident));
+        }
+    }
+
+    /// Returns the value, loading it from the store if necessary.
+    pub fn get(&mut self) -> &T {
+        self.load();
+        self.value.as_ref().expect("value loaded")
+    }
+
+    /// Returns the value mutably, loading it from the store if necessary.
+    /// The value is written back on commit.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.dirty.set(true);
+        self.loaded_mut()
+    }
+
+    /// Returns the value mutably without marking it as changed, for the
+    /// collections to load their contents through.
+    fn loaded_mut(&mut self) -> &mut T {
+        self.load();
+        self.value.as_mut().expect("value loaded")
+    }
+
+    /// Replaces the value.
+    pub fn set(&mut self, value: T) {
+        self.value = Some(value);
+        self.dirty.set(true);
+    }
+
+    /// Writes the value to the store, if it changed.
+    pub fn commit(&mut self) {
+        if self.dirty.get() {
+            let value = self.value.as_ref().expect("dirty values are loaded");
+            self.written(put(&self.store, value));
+        }
+    }
+
+    /// Records where the value was written, until it changes again.
+    fn written(&self, ident: OffsetLen) {
+        self.ident.set(Some(ident));
+        self.dirty.set(false);
+    }
+}
+
+impl<T> Archive for StorageValue<T> {
+    type Archived = ArchivedStorageRef;
+    type Resolver = OffsetLen;
+
+    unsafe fn resolve(&self, _pos: usize, resolver: OffsetLen, out: *mut ArchivedStorageRef) {
+        out.write(ArchivedStorageRef {
+            offset: to_archived!(resolver.offset()),
+            len: to_archived!(resolver.len()),
+        });
+    }
+}
+
+impl<T, S> Serialize<S> for StorageValue<T>
+where
+    T: Archive + Serialize<StoreSerializer<OffsetLen>>,
+    T::Archived: Deserialize<T, StoreContext> + for<'a> CheckBytes<DefaultValidator<'a>>,
+    S: BorrowMut<StoreSerializer<OffsetLen>> + Fallible + ?Sized,
+{
+    fn serialize(&self, serializer: &mut S) -> Result<OffsetLen, S::Error> {
+        // the store is busy serializing the parent, so a changed value is
+        // written through its serializer, as `microkelvin` does for links
+        if self.dirty.get() {
+            let value = self.value.as_ref().expect("dirty values are loaded");
+            let serializer = serializer.borrow_mut();
+            serializer.serialize(value);
+            self.written(serializer.commit());
+        }
+        Ok(self.ident.get().expect("clean values are stored"))
+    }
+}
+
+impl<T> Deserialize<StorageValue<T>, StoreContext> for ArchivedStorageRef {
+    fn deserialize(
+        &self,
+        store: &mut StoreContext,
+    ) -> Result<StorageValue<T>, <StoreContext as Fallible>::Error> {
+        Ok(StorageValue {
+            store: store.clone(),
+            ident: Cell::new(Some(self.ident())),
+            value: None,
+            dirty: Cell::new(false),
+        })
+    }
+}
+
+/// A vector whose elements are loaded from the store one by one
+pub struct StorageVec<T> {
+    elements: StorageValue<Vec<StorageValue<T>>>,
+}
+
+impl<T> StorageVec<T>
+where
+    T: Archive + Serialize<StoreSerializer<OffsetLen>>,
+    T::Archived: Deserialize<T, StoreContext> + for<'a> CheckBytes<DefaultValidator<'a>>,
+{
+    /// Creates an empty vector.
+    pub fn new(store: &StoreContext) -> Self {
+        StorageVec {
+            elements: StorageValue::new(store, Vec::new()),
+        }
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&mut self) -> usize {
+        self.elements.get().len()
+    }
+
+    /// Returns `true` if the vector has no elements.
+    pub fn is_empty(&mut self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the element at `index`, loading it if necessary.
+    pub fn get(&mut self, index: usize) -> Option<&T> {
+        self.elements
+            .loaded_mut()
+            .get_mut(index)
+            .map(|element| element.get())
+    }
+
+    /// Returns the element at `index` mutably, loading it if necessary.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.elements
+            .get_mut()
+            .get_mut(index)
+            .map(|element| element.get_mut())
+    }
+
+    /// Appends an element.
+    pub fn push(&mut self, value: T) {
+        let element = StorageValue::new(&self.elements.store, value);
+        self.elements.get_mut().push(element);
+    }
+
+    /// Removes the last element, returning it.
+    pub fn pop(&mut self) -> Option<T> {
+        let mut element = self.elements.get_mut().pop()?;
+        element.load();
+        element.value
+    }
+
+    /// Writes the changed elements and the vector to the store.
+    pub fn commit(&mut self) {
+        if self.elements.dirty.get() {
+            for element in self.elements.get_mut() {
+                element.commit();
+            }
+        }
+        self.elements.commit();
+    }
+}
+
+impl<T> Archive for StorageVec<T> {
+    type Archived = ArchivedStorageRef;
+    type Resolver = OffsetLen;
+
+    unsafe fn resolve(&self, pos: usize, resolver: OffsetLen, out: *mut ArchivedStorageRef) {
+        self.elements.resolve(pos, resolver, out)
+    }
+}
+
+impl<T, S> Serialize<S> for StorageVec<T>
+where
+    T: Archive + Serialize<StoreSerializer<OffsetLen>>,
+    T::Archived: Deserialize<T, StoreContext> + for<'a> CheckBytes<DefaultValidator<'a>>,
+    S: BorrowMut<StoreSerializer<OffsetLen>> + Fallible + ?Sized,
+{
+    fn serialize(&self, serializer: &mut S) -> Result<OffsetLen, S::Error> {
+        self.elements.serialize(serializer)
+    }
+}
+
+impl<T> Deserialize<StorageVec<T>, StoreContext> for ArchivedStorageRef {
+    fn deserialize(
+        &self,
+        store: &mut StoreContext,
+    ) -> Result<StorageVec<T>, <StoreContext as Fallible>::Error> {
+        Ok(StorageVec {
+            elements: self.deserialize(store)?,
+        })
+    }
+}
+
+/// A map backed by a HAMT, whose nodes are loaded from the store as they
+/// are traversed
+pub struct StorageMap<K, V> {
+    hamt: StorageValue<Hamt<K, V, (), OffsetLen>>,
+}
+
+impl<K, V> StorageMap<K, V>
+where
+    K: Archive<Archived = K> + Clone + Eq + Hash + for<'a> CheckBytes<DefaultValidator<'a>>,
+    V: Archive + Clone,
+    V::Archived: Deserialize<V, StoreContext> + for<'a> CheckBytes<DefaultValidator<'a>>,
+    Hamt<K, V, (), OffsetLen>: Archive + Serialize<StoreSerializer<OffsetLen>>,
+    <Hamt<K, V, (), OffsetLen> as Archive>::Archived: ArchivedCompound<Hamt<K, V, (), OffsetLen>, (), OffsetLen>
+        + Deserialize<Hamt<K, V, (), OffsetLen>, StoreContext>
+        + for<'a> CheckBytes<DefaultValidator<'a>>,
+{
+    /// Creates an empty map.
+    pub fn new(store: &StoreContext) -> Self {
+        StorageMap {
+            hamt: StorageValue::new(store, Hamt::new()),
+        }
+    }
+
+    /// Returns a copy of the value stored under `key`.
+    pub fn get(&mut self, key: &K) -> Option<V> {
+        let store = self.hamt.store.clone();
+        let branch = self.hamt.get().get(key)?;
+
+        let value = match branch.leaf() {
+            MaybeArchived::Memory(value) => value.clone(),
+            MaybeArchived::Archived(value) => deserialize(value, &store),
+        };
+        Some(value)
+    }
+
+    /// Stores a value under `key`, returning the previous one.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.hamt.get_mut().insert(key, value)
+    }
+
+    /// Removes the value stored under `key`, returning it.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        self.hamt.get_mut().remove(key)
+    }
+
+    /// Writes the changed nodes of the map to the store.
+    pub fn commit(&mut self) {
+        self.hamt.commit();
+    }
+}
+
+impl<K, V> Archive for StorageMap<K, V> {
+    type Archived = ArchivedStorageRef;
+    type Resolver = OffsetLen;
+
+    unsafe fn resolve(&self, pos: usize, resolver: OffsetLen, out: *mut ArchivedStorageRef) {
+        self.hamt.resolve(pos, resolver, out)
+    }
+}
+
+impl<K, V, S> Serialize<S> for StorageMap<K, V>
+where
+    Hamt<K, V, (), OffsetLen>: Archive + Serialize<StoreSerializer<OffsetLen>>,
+    <Hamt<K, V, (), OffsetLen> as Archive>::Archived: Deserialize<Hamt<K, V, (), OffsetLen>, StoreContext>
+        + for<'a> CheckBytes<DefaultValidator<'a>>,
+    S: BorrowMut<StoreSerializer<OffsetLen>> + Fallible + ?Sized,
+{
+    fn serialize(&self, serializer: &mut S) -> Result<OffsetLen, S::Error> {
+        self.hamt.serialize(serializer)
+    }
+}
+
+impl<K, V> Deserialize<StorageMap<K, V>, StoreContext> for ArchivedStorageRef {
+    fn deserialize(
+        &self,
+        store: &mut StoreContext,
+    ) -> Result<StorageMap<K, V>, <StoreContext as Fallible>::Error> {
+        Ok(StorageMap {
+            hamt: self.deserialize(store)?,
+        })
+    }
+}
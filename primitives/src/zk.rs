@@ -0,0 +1,52 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Serialized forms of the BLS12-381 and JubJub types passed to the
+//! zero-knowledge friendly host functions.
+
+use bytecheck::CheckBytes;
+use rkyv::{Archive, Deserialize, Serialize};
+
+/// A BLS12-381 scalar, in canonical little endian form
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Archive, Serialize, Deserialize)]
+#[archive_attr(derive(CheckBytes))]
+#[repr(transparent)]
+pub struct BlsScalar(pub [u8; 32]);
+
+/// A JubJub point in compressed affine form
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Archive, Serialize, Deserialize)]
+#[archive_attr(derive(CheckBytes))]
+#[repr(transparent)]
+pub struct JubJubAffine(pub [u8; 32]);
+
+/// A JubJub scalar, in canonical little endian form
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Archive, Serialize, Deserialize)]
+#[archive_attr(derive(CheckBytes))]
+#[repr(transparent)]
+pub struct JubJubScalar(pub [u8; 32]);
+
+/// A BLS12-381 G1 point in compressed affine form
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Archive, Serialize, Deserialize)]
+#[archive_attr(derive(CheckBytes))]
+#[repr(transparent)]
+pub struct G1Affine(pub [u8; 48]);
+
+/// A BLS12-381 G2 point in compressed affine form
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Archive, Serialize, Deserialize)]
+#[archive_attr(derive(CheckBytes))]
+#[repr(transparent)]
+pub struct G2Affine(pub [u8; 96]);
+
+/// A pair of points to compute the pairing of
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Archive, Serialize, Deserialize)]
+#[archive_attr(derive(CheckBytes))]
+#[repr(C)]
+pub struct PairingInput {
+    /// The G1 point
+    pub g1: G1Affine,
+    /// The G2 point
+    pub g2: G2Affine,
+}
@@ -0,0 +1,72 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Runs the guest store against an in-process mock of the `_put` and `_get`
+//! host functions.
+
+mod common;
+
+use microkelvin::{Ident, OffsetLen};
+use primitives::{guest_store, StoreContext};
+use rkyv::Deserialize;
+
+fn roundtrip(store: &StoreContext, value: &Vec<u64>) -> Vec<u64> {
+    let ident = *store.store(value).ident().erase();
+    read(store, ident)
+}
+
+fn read(store: &StoreContext, ident: OffsetLen) -> Vec<u64> {
+    let archived = store.get::<Vec<u64>>(&Ident::new(ident));
+    archived.deserialize(&mut store.clone()).unwrap()
+}
+
+#[test]
+fn small_value() {
+    let store = guest_store();
+    let value: Vec<u64> = (0..16).collect();
+
+    assert_eq!(roundtrip(&store, &value), value);
+}
+
+#[test]
+fn value_larger_than_a_page() {
+    let store = guest_store();
+    let value: Vec<u64> = (0..100_000).collect();
+
+    assert_eq!(roundtrip(&store, &value), value);
+}
+
+#[test]
+fn outstanding_reads() {
+    let store = guest_store();
+    let a: Vec<u64> = (0..1000).collect();
+    let b: Vec<u64> = (1000..2000).collect();
+
+    let ident_a = *store.store(&a).ident().erase();
+    let ident_b = *store.store(&b).ident().erase();
+
+    let archived_a = store.get::<Vec<u64>>(&Ident::new(ident_a));
+    let archived_b = store.get::<Vec<u64>>(&Ident::new(ident_b));
+
+    assert_eq!(archived_a.len(), a.len());
+    assert_eq!(archived_b.len(), b.len());
+    assert_eq!(archived_a[999], 999);
+    assert_eq!(archived_b[999], 1999);
+}
+
+#[test]
+fn write_after_read() {
+    let store = guest_store();
+    let a: Vec<u64> = (0..10).collect();
+    let b: Vec<u64> = (10..20).collect();
+
+    let ident_a = *store.store(&a).ident().erase();
+    let read_a = store.get::<Vec<u64>>(&Ident::new(ident_a));
+    let ident_b = *store.store(&b).ident().erase();
+
+    assert_eq!(read_a.len(), a.len());
+    assert_eq!(read(&store, ident_b), b);
+}
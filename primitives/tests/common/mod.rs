@@ -0,0 +1,50 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! In-process mock of the `_put` and `_get` host functions, counting the
+//! calls made to them. With the `mock` feature, the store goes through the
+//! mock environment instead, so nothing is counted.
+
+#![allow(dead_code)]
+
+use std::cell::{Cell, RefCell};
+
+thread_local! {
+    static HOST_STORE: RefCell<Vec<u8>> = RefCell::new(Vec::new());
+    static PUTS: Cell<usize> = Cell::new(0);
+    static GETS: Cell<usize> = Cell::new(0);
+}
+
+#[no_mangle]
+extern "C" fn _put(slice: *const u8, len: u32) -> u64 {
+    let bytes = unsafe { std::slice::from_raw_parts(slice, len as usize) };
+    PUTS.with(|puts| puts.set(puts.get() + 1));
+    HOST_STORE.with(|store| {
+        let mut store = store.borrow_mut();
+        let offset = store.len() as u64;
+        store.extend_from_slice(bytes);
+        offset
+    })
+}
+
+#[no_mangle]
+extern "C" fn _get(offset: u64, len: u32, buf: *mut u8) {
+    let buf = unsafe { std::slice::from_raw_parts_mut(buf, len as usize) };
+    GETS.with(|gets| gets.set(gets.get() + 1));
+    HOST_STORE.with(|store| {
+        buf.copy_from_slice(&store.borrow()[offset as usize..][..len as usize]);
+    })
+}
+
+/// Returns the number of blobs written to the store by this thread.
+pub fn puts() -> usize {
+    PUTS.with(Cell::get)
+}
+
+/// Returns the number of blobs read from the store by this thread.
+pub fn gets() -> usize {
+    GETS.with(Cell::get)
+}
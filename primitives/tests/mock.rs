@@ -0,0 +1,62 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Runs a `#[bindgen]` function natively against the mock host functions,
+//! with `cargo test --features mock`.
+
+#![cfg(feature = "mock")]
+
+use bindgen_macro::bindgen;
+use microkelvin::Ident;
+use primitives::{env, guest_store, info, mock};
+use rkyv::Deserialize;
+
+const SHA256_ABC: [u8; 32] = [
+    0xba, 0x78, 0x16, 0xbf, 0x8f, 0x01, 0xcf, 0xea, 0x41, 0x41, 0x40, 0xde, 0x5d, 0xae, 0x22, 0x23,
+    0xb0, 0x03, 0x61, 0xa3, 0x96, 0x17, 0x7a, 0x9c, 0xb4, 0x10, 0xff, 0x61, 0xf2, 0x00, 0x15, 0xad,
+];
+
+/// Stores the values and reads them back, logging their sum and the gas
+/// left, then hashes `abc`.
+#[bindgen]
+pub fn store_and_hash(values: Vec<u64>) -> [u8; 32] {
+    let store = guest_store();
+    let ident = *store.store(&values).ident().erase();
+    let read: Vec<u64> = store
+        .get::<Vec<u64>>(&Ident::new(ident))
+        .deserialize(&mut store.clone())
+        .unwrap();
+    assert_eq!(read, values);
+
+    info!("sum: {}", read.iter().sum::<u64>());
+    env::log(format!("gas: {}/{}", env::gas_consumed(), env::gas_left()));
+
+    env::sha256(b"abc")
+}
+
+#[test]
+fn bindgen_function_runs_natively() {
+    mock::reset();
+    mock::set_gas(100, 500);
+
+    assert_eq!(store_and_hash(vec![1, 2, 3]), SHA256_ABC);
+
+    assert_eq!(mock::logs(), ["sum: 6", "gas: 100/500"]);
+    assert_eq!(mock::take_logs().len(), 2);
+    assert!(mock::logs().is_empty());
+}
+
+#[test]
+fn environment_is_reset() {
+    mock::set_gas(100, 500);
+    env::log("before reset");
+
+    mock::reset();
+
+    assert!(mock::logs().is_empty());
+    assert_eq!(env::gas_consumed(), 0);
+    assert_eq!(env::gas_left(), u64::MAX);
+}
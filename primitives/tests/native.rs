@@ -0,0 +1,229 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Known answer tests of the native host function implementations, with
+//! `cargo test --features native`.
+
+#![cfg(feature = "native")]
+
+use primitives::native::{
+    blake2b, keccak256, recover_secp256k1, sha512, verify_ed25519, verify_secp256k1, Hasher,
+};
+use primitives::HashAlgorithm;
+
+fn hex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn array<const N: usize>(s: &str) -> [u8; N] {
+    let mut array = [0u8; N];
+    array.copy_from_slice(&hex(s));
+    array
+}
+
+/// Public key, message and signature of the tests in RFC 8032, 7.1
+const RFC8032: [(&str, &str, &str); 3] = [
+    (
+        "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        "",
+        "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e06522490155\
+         5fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+    ),
+    (
+        "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+        "72",
+        "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da\
+         085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00",
+    ),
+    (
+        "fc51cd8e6218a1a38da47ed00230f0580816ed13ba3303ac5deb911548908025",
+        "af82",
+        "6291d657deec24024827e69c3abe01a30ce548a284743a445e3680d7db5ac3ac\
+         18ff9b538d16f290ae67f760984dc6594a7c15e9716ed28dc027beceea1ec40a",
+    ),
+];
+
+/// Message hash, signature with recovery id and public key of an
+/// Ethereum transaction, from the tests of go-ethereum
+const ETH_MSG: &str = "ce0677bb30baa8cf067c88db9811f4333d131bf8bcf12fe7065d211dce971008";
+const ETH_SIG: &str = "90f27b8b488db00b00606796d2987f6a5f59ae62ea05effe84fef5b8b0e54998\
+                       4a691139ad57a3f0b906637673aa2f63d1f55cb1a69199d4009eea23ceaddc93";
+const ETH_PK: &str = "04e32df42865e97135acfb65f3bae71bdc86f4d49150ad6a440b6f15878109880a\
+                      0a2b2667f7e725ceea70c673093bf67663e0312623c8e091b13cf2c0f11ef652";
+/// Public key recovered from the signature with the other recovery id
+const ETH_OTHER_PK: &str = "04f6928faf30a8be548dcef6f86cf290ebe93d1483c0a4becb16fb110c1f9607f1\
+                            3679dd1b9879e616e13f6a31fb0489800fe7d2bab79bce18c54ebb210b6bb7ee";
+
+#[test]
+fn ed25519_rfc8032() {
+    for (pk, msg, sig) in RFC8032 {
+        assert!(verify_ed25519(&array(pk), &hex(msg), &array(sig)));
+    }
+}
+
+#[test]
+fn ed25519_invalid_signature() {
+    let (pk, msg, sig) = RFC8032[1];
+
+    let mut sig: [u8; 64] = array(sig);
+    sig[0] ^= 1;
+    assert!(!verify_ed25519(&array(pk), &hex(msg), &sig));
+
+    let (_, _, sig) = RFC8032[1];
+    assert!(!verify_ed25519(&array(pk), b"wrong message", &array(sig)));
+}
+
+#[test]
+fn ed25519_malformed_key() {
+    let (_, msg, sig) = RFC8032[1];
+
+    // y = 2 has no x on the curve
+    let mut pk = [0u8; 32];
+    pk[0] = 2;
+    assert!(!verify_ed25519(&pk, &hex(msg), &array(sig)));
+}
+
+#[test]
+fn ed25519_weak_key() {
+    // the identity as key and commitment, with a zero scalar, satisfies
+    // the verification equation for any message
+    let mut identity = [0u8; 32];
+    identity[0] = 1;
+    let mut sig = [0u8; 64];
+    sig[..32].copy_from_slice(&identity);
+
+    assert!(!verify_ed25519(&identity, b"any message", &sig));
+}
+
+#[test]
+fn secp256k1_verify() {
+    let pk = hex(ETH_PK);
+    assert!(verify_secp256k1(&pk, &array(ETH_MSG), &array(ETH_SIG)));
+
+    // compressed, the y coordinate is even
+    let mut compressed = vec![2];
+    compressed.extend_from_slice(&pk[1..33]);
+    assert!(verify_secp256k1(
+        &compressed,
+        &array(ETH_MSG),
+        &array(ETH_SIG)
+    ));
+}
+
+#[test]
+fn secp256k1_invalid_signature() {
+    let mut msg: [u8; 32] = array(ETH_MSG);
+    msg[0] ^= 1;
+    assert!(!verify_secp256k1(&hex(ETH_PK), &msg, &array(ETH_SIG)));
+
+    let mut sig: [u8; 64] = array(ETH_SIG);
+    sig[40] ^= 1;
+    assert!(!verify_secp256k1(&hex(ETH_PK), &array(ETH_MSG), &sig));
+}
+
+#[test]
+fn secp256k1_malformed_key() {
+    let mut pk = hex(ETH_PK);
+    pk[64] ^= 1;
+    assert!(!verify_secp256k1(&pk, &array(ETH_MSG), &array(ETH_SIG)));
+
+    assert!(!verify_secp256k1(
+        &pk[..40],
+        &array(ETH_MSG),
+        &array(ETH_SIG)
+    ));
+}
+
+#[test]
+fn secp256k1_recover() {
+    let mut sig = [0u8; 65];
+    sig[..64].copy_from_slice(&hex(ETH_SIG));
+
+    for (v, pk) in [
+        (0, ETH_OTHER_PK),
+        (1, ETH_PK),
+        (27, ETH_OTHER_PK),
+        (28, ETH_PK),
+    ] {
+        sig[64] = v;
+        let recovered = recover_secp256k1(&array(ETH_MSG), &sig);
+        assert_eq!(recovered, Some(array(pk)), "recovery id {}", v);
+    }
+}
+
+#[test]
+fn secp256k1_recover_invalid_signature() {
+    let mut sig = [0u8; 65];
+    sig[64] = 27;
+    assert_eq!(recover_secp256k1(&array(ETH_MSG), &sig), None);
+}
+
+const BLAKE2B_ABC: &str = "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+                           7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923";
+const SHA256_ABC: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+const KECCAK256_ABC: &str = "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45";
+const SHA512_ABC: &str = "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a\
+                          2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f";
+
+#[test]
+fn blake2b_abc() {
+    assert_eq!(blake2b(b"abc", &[], 64).as_bytes(), &hex(BLAKE2B_ABC)[..]);
+}
+
+#[test]
+fn blake2b_digest_length() {
+    assert_eq!(
+        blake2b(b"abc", &[], 32).as_bytes(),
+        &hex("bddd813c634239723171ef3fee98579b94964e3bb1cb3e427262c8c068d52319")[..]
+    );
+}
+
+#[test]
+fn blake2b_personalization() {
+    assert_eq!(
+        blake2b(b"abc", b"dusk-network", 32).as_bytes(),
+        &hex("ccbbad0ad4a53b06f62b0265e84c2437ef23cd2e6637e0a6029ec8e54357c883")[..]
+    );
+}
+
+#[test]
+fn keccak256_known_answers() {
+    assert_eq!(
+        keccak256(b"")[..],
+        hex("c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470")[..]
+    );
+    assert_eq!(keccak256(b"abc")[..], hex(KECCAK256_ABC)[..]);
+}
+
+#[test]
+fn sha512_known_answers() {
+    assert_eq!(
+        sha512(b"")[..],
+        hex(
+            "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce\
+             47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e"
+        )[..]
+    );
+    assert_eq!(sha512(b"abc")[..], hex(SHA512_ABC)[..]);
+}
+
+#[test]
+fn hasher_matches_known_answers() {
+    for (algorithm, digest) in [
+        (HashAlgorithm::Sha256, SHA256_ABC),
+        (HashAlgorithm::Blake2b, BLAKE2B_ABC),
+        (HashAlgorithm::Keccak256, KECCAK256_ABC),
+        (HashAlgorithm::Sha512, SHA512_ABC),
+    ] {
+        let mut hasher = Hasher::new(algorithm);
+        hasher.update(b"a");
+        hasher.update(b"bc");
+        assert_eq!(hasher.finalize(), hex(digest));
+    }
+}
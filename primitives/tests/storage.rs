@@ -0,0 +1,145 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Runs the storage collections against an in-process mock of the `_put`
+//! and `_get` host functions.
+
+mod common;
+
+use bytecheck::CheckBytes;
+use microkelvin::{Ident, OffsetLen, StoreSerializer};
+use primitives::{guest_store, StorageMap, StorageValue, StorageVec, StoreContext};
+use rkyv::validation::validators::DefaultValidator;
+use rkyv::{Archive, Deserialize, Serialize};
+
+/// Writes a collection to the store, as part of the state of a contract.
+fn put<T>(store: &StoreContext, value: &T) -> OffsetLen
+where
+    T: Serialize<StoreSerializer<OffsetLen>>,
+{
+    *store.store(value).ident().erase()
+}
+
+/// Reads a collection back, as when the state of a contract is loaded.
+fn reload<T>(store: &StoreContext, ident: OffsetLen) -> T
+where
+    T: Archive,
+    T::Archived: Deserialize<T, StoreContext> + for<'a> CheckBytes<DefaultValidator<'a>>,
+{
+    let archived = store.get::<T>(&Ident::new(ident));
+    archived.deserialize(&mut store.clone()).unwrap()
+}
+
+#[test]
+fn value_commit_and_reload() {
+    let store = guest_store();
+    let mut value = StorageValue::new(&store, vec![1u64, 2, 3]);
+    value.commit();
+
+    let mut value: StorageValue<Vec<u64>> = reload(&store, put(&store, &value));
+    assert_eq!(value.get(), &vec![1, 2, 3]);
+
+    value.get_mut().push(4);
+    let mut value: StorageValue<Vec<u64>> = reload(&store, put(&store, &value));
+    assert_eq!(value.get(), &vec![1, 2, 3, 4]);
+}
+
+#[test]
+#[cfg(not(feature = "mock"))]
+fn value_is_loaded_lazily() {
+    let store = guest_store();
+    let value = StorageValue::new(&store, vec![1u64, 2, 3]);
+    let ident = put(&store, &value);
+
+    let mut value: StorageValue<Vec<u64>> = reload(&store, ident);
+    assert!(format!("{:?}", value).contains("value: None"));
+
+    let gets = common::gets();
+    assert_eq!(value.get(), &vec![1, 2, 3]);
+    assert_eq!(common::gets(), gets + 1);
+
+    value.get();
+    assert_eq!(common::gets(), gets + 1, "A loaded value is not read again");
+}
+
+#[test]
+#[cfg(not(feature = "mock"))]
+fn dirty_value_is_written_once() {
+    let store = guest_store();
+    let mut value = StorageValue::new(&store, vec![1u64, 2, 3]);
+
+    // the value, then the reference to it
+    let puts = common::puts();
+    let first = put(&store, &value);
+    assert_eq!(common::puts(), puts + 2);
+
+    let second = put(&store, &value);
+    assert_eq!(common::puts(), puts + 3);
+    value.commit();
+    assert_eq!(common::puts(), puts + 3);
+
+    let mut first: StorageValue<Vec<u64>> = reload(&store, first);
+    let mut second: StorageValue<Vec<u64>> = reload(&store, second);
+    assert_eq!(first.get(), second.get());
+
+    value.set(vec![4]);
+    value.commit();
+    assert_eq!(common::puts(), puts + 4);
+}
+
+#[test]
+fn vec_push_pop() {
+    let store = guest_store();
+    let mut vec = StorageVec::new(&store);
+    for i in 0..4u64 {
+        vec.push(i);
+    }
+    vec.commit();
+
+    let mut vec: StorageVec<u64> = reload(&store, put(&store, &vec));
+    assert_eq!(vec.len(), 4);
+    assert_eq!(vec.get(2), Some(&2));
+    assert_eq!(vec.get(4), None);
+
+    *vec.get_mut(1).unwrap() = 10;
+    assert_eq!(vec.pop(), Some(3));
+    vec.push(5);
+
+    let mut vec: StorageVec<u64> = reload(&store, put(&store, &vec));
+    assert_eq!(vec.len(), 4);
+    assert_eq!(vec.get(1), Some(&10));
+    assert_eq!(vec.pop(), Some(5));
+    assert_eq!(vec.pop(), Some(2));
+    assert_eq!(vec.pop(), Some(10));
+    assert_eq!(vec.pop(), Some(0));
+    assert_eq!(vec.pop(), None);
+    assert!(vec.is_empty());
+}
+
+#[test]
+fn map_insert_remove() {
+    let store = guest_store();
+    let mut map = StorageMap::new(&store);
+    for i in 0..64u64 {
+        assert_eq!(map.insert(i, i * 2), None);
+    }
+    assert_eq!(map.insert(7, 70), Some(14));
+    map.commit();
+
+    let mut map: StorageMap<u64, u64> = reload(&store, put(&store, &map));
+    assert_eq!(map.get(&7), Some(70));
+    assert_eq!(map.get(&63), Some(126));
+    assert_eq!(map.get(&64), None);
+
+    assert_eq!(map.remove(&7), Some(70));
+    assert_eq!(map.remove(&7), None);
+    map.insert(64, 128);
+
+    let mut map: StorageMap<u64, u64> = reload(&store, put(&store, &map));
+    assert_eq!(map.get(&7), None);
+    assert_eq!(map.get(&8), Some(16));
+    assert_eq!(map.get(&64), Some(128));
+}
@@ -0,0 +1,111 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Building contracts from a build script, so tests can deploy them.
+//!
+//! ```ignore
+//! // build.rs
+//! fn main() {
+//!     vm_test::build_contract("../my-contract");
+//! }
+//!
+//! // tests/my_contract.rs
+//! let contract = TestVm::new().deploy(env!("CONTRACT_MY_CONTRACT"));
+//! ```
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Target contracts are compiled for
+pub const CONTRACT_TARGET: &str = "wasm32-unknown-unknown";
+
+/// Builds the contract crate in `manifest_dir`, relative to the crate of the
+/// running build script, for [`CONTRACT_TARGET`] in release mode. Returns
+/// the path of the wasm file.
+///
+/// The path is also passed to the crate being built as the
+/// `CONTRACT_<NAME>` environment variable, `<NAME>` being the package name
+/// in upper case with dashes replaced by underscores.
+///
+/// Contracts are built in their own target directory under `OUT_DIR`, since
+/// the one of the running build is locked. The build is rerun when the
+/// sources or the manifest of the contract change, but not when its path
+/// dependencies do.
+///
+/// # Panics
+///
+/// If not run from a build script, or if the contract fails to build.
+pub fn build_contract(manifest_dir: impl AsRef<Path>) -> PathBuf {
+    let crate_dir = env::var_os("CARGO_MANIFEST_DIR").expect("Not run from a build script");
+    let out_dir = env::var_os("OUT_DIR").expect("Not run from a build script");
+
+    let manifest_dir = Path::new(&crate_dir).join(manifest_dir);
+    let manifest_path = manifest_dir.join("Cargo.toml");
+    let manifest = fs::read_to_string(&manifest_path)
+        .unwrap_or_else(|e| panic!("Failed to read {}: {}", manifest_path.display(), e));
+    let name = package_name(&manifest)
+        .unwrap_or_else(|| panic!("No package name in {}", manifest_path.display()));
+
+    let target_dir = Path::new(&out_dir).join("contracts");
+    let cargo = env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+    let status = Command::new(cargo)
+        .arg("build")
+        .arg("--release")
+        .arg("--target")
+        .arg(CONTRACT_TARGET)
+        .arg("--manifest-path")
+        .arg(&manifest_path)
+        .arg("--target-dir")
+        .arg(&target_dir)
+        // the flags of the running build are meant for the host
+        .env_remove("CARGO_ENCODED_RUSTFLAGS")
+        .env_remove("RUSTFLAGS")
+        .env_remove("CARGO_TARGET_DIR")
+        .status()
+        .expect("Failed to run cargo");
+    assert!(status.success(), "Failed to build contract {}", name);
+
+    let wasm = target_dir
+        .join(CONTRACT_TARGET)
+        .join("release")
+        .join(format!("{}.wasm", name.replace('-', "_")));
+
+    println!(
+        "cargo:rustc-env=CONTRACT_{}={}",
+        name.to_uppercase().replace('-', "_"),
+        wasm.display()
+    );
+    println!("cargo:rerun-if-changed={}", manifest_path.display());
+    println!(
+        "cargo:rerun-if-changed={}",
+        manifest_dir.join("src").display()
+    );
+
+    wasm
+}
+
+/// Returns the name in the `[package]` section of a manifest.
+fn package_name(manifest: &str) -> Option<String> {
+    let mut in_package = false;
+    for line in manifest.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_package = line == "[package]";
+            continue;
+        }
+        if !in_package {
+            continue;
+        }
+
+        let mut parts = line.splitn(2, '=');
+        if parts.next().map(str::trim) == Some("name") {
+            let value = parts.next()?.trim();
+            return Some(value.trim_matches('"').to_string());
+        }
+    }
+    None
+}
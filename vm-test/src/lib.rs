@@ -0,0 +1,52 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! #VM-Test
+//!
+//! Harness for integration tests running compiled contracts through
+//! [`vm::Vm`], the same way they run in production.
+//!
+//! ```ignore
+//! use vm::VMError;
+//! use vm_test::{assert_vm_error, TestVm};
+//!
+//! let contract = TestVm::new()
+//!     .with_gas_limit(1_000_000)
+//!     .deploy(env!("CONTRACT_COMPILE"));
+//!
+//! contract
+//!     .execute("invoke")
+//!     .assert_ok()
+//!     .assert_debug_contains("hello");
+//!
+//! assert_vm_error!(contract.execute("missing"), VMError::WasmerExportError(_));
+//! ```
+#![warn(missing_docs)]
+
+pub mod build;
+mod outcome;
+mod test_vm;
+
+pub use build::build_contract;
+pub use outcome::Outcome;
+pub use test_vm::{TestContract, TestVm, DEFAULT_GAS_LIMIT};
+
+pub use vm;
+
+/// Asserts the [`Outcome`] of an execution is an error matching the given
+/// [`VMError`](vm::VMError) pattern.
+#[macro_export]
+macro_rules! assert_vm_error {
+    ($outcome:expr, $pattern:pat) => {{
+        let error = $outcome.error();
+        assert!(
+            matches!(error, $pattern),
+            "expected error matching `{}`, got: {:?}",
+            stringify!($pattern),
+            error
+        );
+    }};
+}
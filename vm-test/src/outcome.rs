@@ -0,0 +1,171 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Outcome of an execution, with assertions on it.
+
+use vm::{Event, Gas, VMError};
+
+/// The outcome of running a contract: its result, the gas it spent, its
+/// debug output and the events it emitted.
+///
+/// The assertions panic with a description of the mismatch, and return the
+/// outcome so they can be chained.
+#[derive(Debug)]
+pub struct Outcome<T> {
+    result: Result<T, VMError>,
+    gas_spent: Gas,
+    debug: Vec<String>,
+    events: Vec<Event>,
+}
+
+impl<T> Outcome<T> {
+    pub(crate) fn new(
+        result: Result<T, VMError>,
+        gas_spent: Gas,
+        debug: Vec<String>,
+        events: Vec<Event>,
+    ) -> Self {
+        Outcome {
+            result,
+            gas_spent,
+            debug,
+            events,
+        }
+    }
+
+    /// Converts the value of a successful execution.
+    pub(crate) fn map<U>(self, f: impl FnOnce(T) -> U) -> Outcome<U> {
+        self.and_then(|value| Ok(f(value)))
+    }
+
+    /// Converts the value of a successful execution, failing the outcome if
+    /// the conversion does.
+    pub(crate) fn and_then<U>(self, f: impl FnOnce(T) -> Result<U, VMError>) -> Outcome<U> {
+        Outcome {
+            result: self.result.and_then(f),
+            gas_spent: self.gas_spent,
+            debug: self.debug,
+            events: self.events,
+        }
+    }
+
+    /// Returns the result of the execution.
+    pub fn result(&self) -> Result<&T, &VMError> {
+        self.result.as_ref()
+    }
+
+    /// Returns the value the execution succeeded with.
+    ///
+    /// # Panics
+    ///
+    /// If the execution failed.
+    pub fn value(&self) -> &T {
+        match &self.result {
+            Ok(value) => value,
+            Err(e) => panic!("expected success, got error: {}", e),
+        }
+    }
+
+    /// Returns the value the execution succeeded with, consuming the outcome.
+    ///
+    /// # Panics
+    ///
+    /// If the execution failed.
+    pub fn into_value(self) -> T {
+        match self.result {
+            Ok(value) => value,
+            Err(e) => panic!("expected success, got error: {}", e),
+        }
+    }
+
+    /// Returns the error the execution failed with.
+    ///
+    /// # Panics
+    ///
+    /// If the execution succeeded.
+    pub fn error(&self) -> &VMError {
+        match &self.result {
+            Ok(_) => panic!("expected an error, got success"),
+            Err(e) => e,
+        }
+    }
+
+    /// Returns the gas spent by the execution, whether it succeeded or not.
+    pub fn gas_spent(&self) -> Gas {
+        self.gas_spent
+    }
+
+    /// Returns the debug messages of the execution, whether it succeeded or
+    /// not.
+    pub fn debug(&self) -> &[String] {
+        &self.debug
+    }
+
+    /// Returns the events emitted by the execution, empty if it failed.
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// Asserts the execution succeeded.
+    pub fn assert_ok(&self) -> &Self {
+        self.value();
+        self
+    }
+
+    /// Asserts the execution spent exactly `gas`.
+    pub fn assert_gas_spent(&self, gas: Gas) -> &Self {
+        assert_eq!(self.gas_spent, gas, "unexpected gas spent");
+        self
+    }
+
+    /// Asserts the execution spent at most `gas`.
+    pub fn assert_gas_at_most(&self, gas: Gas) -> &Self {
+        assert!(
+            self.gas_spent <= gas,
+            "expected at most {} gas spent, got {}",
+            gas,
+            self.gas_spent
+        );
+        self
+    }
+
+    /// Asserts the debug messages of the execution are exactly `messages`.
+    pub fn assert_debug(&self, messages: &[&str]) -> &Self {
+        assert_eq!(self.debug, messages, "unexpected debug output");
+        self
+    }
+
+    /// Asserts a debug message of the execution contains `pattern`.
+    pub fn assert_debug_contains(&self, pattern: &str) -> &Self {
+        assert!(
+            self.debug.iter().any(|message| message.contains(pattern)),
+            "no debug message contains {:?}, got: {:?}",
+            pattern,
+            self.debug
+        );
+        self
+    }
+
+    /// Asserts the execution emitted exactly `count` events.
+    pub fn assert_event_count(&self, count: usize) -> &Self {
+        assert_eq!(self.events.len(), count, "unexpected number of events");
+        self
+    }
+
+    /// Asserts the execution emitted an event with the given topic and
+    /// archived data.
+    pub fn assert_event(&self, topic: &str, data: &[u8]) -> &Self {
+        assert!(
+            self.events
+                .iter()
+                .any(|event| event.topic() == topic && event.data() == data),
+            "no event with topic {:?} and the given data, got: {:?}",
+            topic,
+            self.events
+        );
+        self
+    }
+}
@@ -0,0 +1,247 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Builder of virtual machines running a contract under test.
+
+use std::fs;
+use std::path::Path;
+
+use bytecheck::CheckBytes;
+use rkyv::ser::serializers::AllocSerializer;
+use rkyv::validation::validators::DefaultValidator;
+use rkyv::{Archive, Deserialize, Infallible, Serialize};
+use vm::primitives::{ContractId, Query, ReturnValue, Transaction};
+use vm::{BufferSink, Config, Gas, GasMeter, HostFunction, Receipt, VMError, Vm};
+
+use crate::outcome::Outcome;
+
+/// Gas limit of each execution, unless specified otherwise
+pub const DEFAULT_GAS_LIMIT: Gas = 10_000_000;
+
+static DEFAULT_CONFIG: Config = Config::new();
+
+/// Size of the scratch space used when archiving states and arguments
+const ARCHIVE_SCRATCH_SIZE: usize = 1024;
+
+/// Builder of a [`Vm`] to run a contract under test with.
+pub struct TestVm {
+    config: &'static Config,
+    host_functions: Vec<HostFunction>,
+    gas_limit: Gas,
+}
+
+impl TestVm {
+    /// Returns a builder with the default configuration and
+    /// [`DEFAULT_GAS_LIMIT`].
+    pub fn new() -> Self {
+        TestVm {
+            config: &DEFAULT_CONFIG,
+            host_functions: Vec::new(),
+            gas_limit: DEFAULT_GAS_LIMIT,
+        }
+    }
+
+    /// Sets the configuration of the [`Vm`].
+    pub fn with_config(mut self, config: &'static Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Registers a host function with the [`Vm`], see
+    /// [`Vm::register_host_function`].
+    pub fn with_host_function(mut self, function: HostFunction) -> Self {
+        self.host_functions.push(function);
+        self
+    }
+
+    /// Sets the gas limit of each execution.
+    pub fn with_gas_limit(mut self, gas_limit: Gas) -> Self {
+        self.gas_limit = gas_limit;
+        self
+    }
+
+    /// Builds the [`Vm`] with the contract in the wasm file at `path`.
+    ///
+    /// # Panics
+    ///
+    /// If the file cannot be read.
+    pub fn deploy(self, path: impl AsRef<Path>) -> TestContract {
+        self.deploy_code(read_code(path.as_ref()))
+    }
+
+    /// Builds the [`Vm`] with the contract in `code`.
+    pub fn deploy_code(self, code: impl Into<Vec<u8>>) -> TestContract {
+        let sink = BufferSink::new();
+
+        let mut vm = Vm::with_config(self.config).with_debug_sink(sink.clone());
+        for function in self.host_functions {
+            vm.register_host_function(function);
+        }
+
+        TestContract {
+            vm,
+            code: code.into(),
+            sink,
+            gas_limit: self.gas_limit,
+        }
+    }
+}
+
+impl Default for TestVm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A contract under test, with the [`Vm`] it runs in.
+///
+/// Each execution gets a fresh [`GasMeter`] and its own debug output.
+pub struct TestContract {
+    vm: Vm,
+    code: Vec<u8>,
+    sink: BufferSink,
+    gas_limit: Gas,
+}
+
+impl TestContract {
+    /// Returns the [`Vm`] the contract runs in.
+    pub fn vm(&self) -> &Vm {
+        &self.vm
+    }
+
+    /// Returns the code of the contract.
+    pub fn code(&self) -> &[u8] {
+        &self.code
+    }
+
+    /// Sets the gas limit of the following executions.
+    pub fn set_gas_limit(&mut self, gas_limit: Gas) {
+        self.gas_limit = gas_limit;
+    }
+
+    /// Deploys the contract in the wasm file at `path` with its initial
+    /// archived state, so the contract under test can call it.
+    ///
+    /// # Panics
+    ///
+    /// If the file cannot be read.
+    pub fn deploy(&mut self, path: impl AsRef<Path>, state: Vec<u8>) -> ContractId {
+        let code = read_code(path.as_ref());
+        self.vm.deploy(&code, state)
+    }
+
+    /// Executes a function of the contract without arguments or outputs,
+    /// see [`Vm::execute`].
+    pub fn execute(&self, entrypoint: &str) -> Outcome<()> {
+        self.run(|vm, code, gas_meter| vm.execute(code, entrypoint, gas_meter))
+            .map(|_| ())
+    }
+
+    /// Calls a function of the contract with the archived `state` and `arg`,
+    /// see [`Vm::call`].
+    pub fn call(&self, entrypoint: &str, state: &[u8], arg: &[u8]) -> Outcome<ReturnValue> {
+        self.run(|vm, code, gas_meter| vm.call(code, entrypoint, state, arg, gas_meter))
+            .map(|receipt| {
+                let ret = receipt.return_value();
+                ReturnValue::with_state(ret.data(), ret.state())
+            })
+    }
+
+    /// Runs the query `Q` against the contract in the given state, see
+    /// [`Vm::query`].
+    pub fn query<S, Q>(&self, state: &S, query: Q) -> Outcome<Q::Return>
+    where
+        S: Serialize<AllocSerializer<ARCHIVE_SCRATCH_SIZE>>,
+        Q: Query + Serialize<AllocSerializer<ARCHIVE_SCRATCH_SIZE>>,
+        Q::Return: Archive,
+        <Q::Return as Archive>::Archived:
+            for<'b> CheckBytes<DefaultValidator<'b>> + Deserialize<Q::Return, Infallible>,
+    {
+        self.run(|vm, code, gas_meter| {
+            vm.call(
+                code,
+                Q::NAME,
+                &archive(state)?,
+                &archive(&query)?,
+                gas_meter,
+            )
+        })
+        .and_then(|receipt| deserialize(receipt.return_value().data()))
+    }
+
+    /// Applies the transaction `T` to the contract in the given state,
+    /// updating the state if it succeeds, see [`Vm::transact`].
+    pub fn transact<S, T>(&self, state: &mut S, transaction: T) -> Outcome<T::Return>
+    where
+        S: Archive + Serialize<AllocSerializer<ARCHIVE_SCRATCH_SIZE>>,
+        S::Archived: for<'b> CheckBytes<DefaultValidator<'b>> + Deserialize<S, Infallible>,
+        T: Transaction + Serialize<AllocSerializer<ARCHIVE_SCRATCH_SIZE>>,
+        T::Return: Archive,
+        <T::Return as Archive>::Archived:
+            for<'b> CheckBytes<DefaultValidator<'b>> + Deserialize<T::Return, Infallible>,
+    {
+        self.run(|vm, code, gas_meter| {
+            vm.call(
+                code,
+                T::NAME,
+                &archive(&*state)?,
+                &archive(&transaction)?,
+                gas_meter,
+            )
+        })
+        .and_then(|receipt| {
+            let ret = deserialize(receipt.return_value().data())?;
+            *state = deserialize(receipt.return_value().state())?;
+            Ok(ret)
+        })
+    }
+
+    /// Runs `f` with a fresh gas meter, collecting the debug output and the
+    /// events.
+    fn run<F>(&self, f: F) -> Outcome<Receipt>
+    where
+        F: FnOnce(&Vm, &[u8], &mut GasMeter) -> Result<Receipt, VMError>,
+    {
+        // drop the output of previous executions
+        self.sink.take();
+
+        let mut gas_meter = GasMeter::with_limit(self.gas_limit);
+        let result = f(&self.vm, &self.code, &mut gas_meter);
+
+        let events = match &result {
+            Ok(receipt) => receipt.events().to_vec(),
+            Err(_) => Vec::new(),
+        };
+        Outcome::new(result, gas_meter.spent(), self.sink.take(), events)
+    }
+}
+
+fn read_code(path: &Path) -> Vec<u8> {
+    fs::read(path).unwrap_or_else(|e| panic!("Failed to read {}: {}", path.display(), e))
+}
+
+fn archive<T>(value: &T) -> Result<Vec<u8>, VMError>
+where
+    T: Serialize<AllocSerializer<ARCHIVE_SCRATCH_SIZE>>,
+{
+    rkyv::to_bytes::<_, ARCHIVE_SCRATCH_SIZE>(value)
+        .map(|bytes| bytes.to_vec())
+        .map_err(|_| VMError::InvalidData)
+}
+
+fn deserialize<T>(bytes: &[u8]) -> Result<T, VMError>
+where
+    T: Archive,
+    T::Archived: for<'b> CheckBytes<DefaultValidator<'b>> + Deserialize<T, Infallible>,
+{
+    let mut aligned = rkyv::AlignedVec::new();
+    aligned.extend_from_slice(bytes);
+
+    rkyv::check_archived_root::<T>(&aligned)
+        .map_err(|_| VMError::InvalidData)?
+        .deserialize(&mut Infallible)
+        .map_err(|_| VMError::InvalidData)
+}
@@ -0,0 +1,272 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Machine-readable description of the functions a contract exports, as
+//! emitted by `bindgen-macro`.
+
+use std::convert::TryInto;
+
+use serde::{Deserialize, Serialize};
+use wasmparser::{Parser, Payload};
+
+use crate::VMError;
+
+/// Name of the custom section the ABI is read from.
+pub const ABI_SECTION: &str = "vm_abi";
+
+/// Whether an export reads or changes the state of a contract.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FunctionKind {
+    /// A free function exported with `#[bindgen]`
+    Function,
+    /// A `&self` method of a `#[contract]`
+    Query,
+    /// A `&mut self` method of a `#[contract]`
+    Transaction,
+}
+
+/// The description of the type of an argument or return value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TypeAbi {
+    /// Name of the type, as written in the contract source
+    pub name: String,
+    /// Size of the archived type in the guest, in bytes
+    pub size: u32,
+    /// Alignment of the archived type in the guest, in bytes
+    pub align: u32,
+}
+
+/// The description of an exported function.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FunctionAbi {
+    /// Name of the function, which is exported as `__vm_<name>`
+    pub name: String,
+    /// Kind of the function
+    pub kind: FunctionKind,
+    /// Types of the arguments
+    pub args: Vec<TypeAbi>,
+    /// Type of the return value
+    pub returns: TypeAbi,
+}
+
+/// The JSON part of an ABI record, naming the types.
+#[derive(Deserialize)]
+struct RecordJson {
+    name: String,
+    kind: FunctionKind,
+    args: Vec<String>,
+    returns: String,
+}
+
+/// The description of all the functions a contract exports.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractAbi {
+    functions: Vec<FunctionAbi>,
+}
+
+impl ContractAbi {
+    /// Returns the exported functions, in the order they were linked.
+    pub fn functions(&self) -> &[FunctionAbi] {
+        &self.functions
+    }
+
+    /// Returns the function exported under the given name.
+    pub fn function(&self, name: &str) -> Option<&FunctionAbi> {
+        self.functions.iter().find(|f| f.name == name)
+    }
+
+    /// Renders the ABI as JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("ABI should serialize to JSON")
+    }
+}
+
+/// Reads the ABI of a contract from its bytecode. Contracts built without
+/// `bindgen-macro` have an empty ABI.
+pub fn read(code: &[u8]) -> Result<ContractAbi, VMError> {
+    let mut abi = ContractAbi::default();
+
+    for payload in Parser::new(0).parse_all(code) {
+        match payload.map_err(|_| VMError::InvalidWASMModule)? {
+            Payload::CustomSection { name, data, .. } if name == ABI_SECTION => {
+                let mut records = data;
+                while !records.is_empty() {
+                    let function = read_record(&mut records).ok_or(VMError::InvalidData)?;
+                    abi.functions.push(function);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(abi)
+}
+
+/// Reads the record of a function off the front of `data`: the length of
+/// its JSON description, the JSON, then the size and alignment of each type
+/// it names.
+fn read_record(data: &mut &[u8]) -> Option<FunctionAbi> {
+    let json_len = read_u32(data)? as usize;
+    let json: RecordJson = serde_json::from_slice(take(data, json_len)?).ok()?;
+
+    let mut read_type = |name: String| {
+        Some(TypeAbi {
+            name,
+            size: read_u32(data)?,
+            align: read_u32(data)?,
+        })
+    };
+    let args = json
+        .args
+        .into_iter()
+        .map(&mut read_type)
+        .collect::<Option<Vec<_>>>()?;
+    let returns = read_type(json.returns)?;
+
+    Some(FunctionAbi {
+        name: json.name,
+        kind: json.kind,
+        args,
+        returns,
+    })
+}
+
+fn read_u32(data: &mut &[u8]) -> Option<u32> {
+    Some(u32::from_le_bytes(take(data, 4)?.try_into().ok()?))
+}
+
+fn take<'a>(data: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+    if data.len() < len {
+        return None;
+    }
+    let (head, tail) = data.split_at(len);
+    *data = tail;
+    Some(head)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a module holding only a `vm_abi` custom section with the
+    /// given contents.
+    fn module(section: &[u8]) -> Vec<u8> {
+        let mut payload = vec![ABI_SECTION.len() as u8];
+        payload.extend_from_slice(ABI_SECTION.as_bytes());
+        payload.extend_from_slice(section);
+
+        let mut code = b"\0asm\x01\0\0\0".to_vec();
+        code.push(0);
+        let mut len = payload.len();
+        loop {
+            let byte = (len & 0x7f) as u8;
+            len >>= 7;
+            if len == 0 {
+                code.push(byte);
+                break;
+            }
+            code.push(byte | 0x80);
+        }
+        code.extend_from_slice(&payload);
+        code
+    }
+
+    /// Builds the record of a function, with the given layouts.
+    fn record(json: &str, layouts: &[(u32, u32)]) -> Vec<u8> {
+        let mut record = (json.len() as u32).to_le_bytes().to_vec();
+        record.extend_from_slice(json.as_bytes());
+        for (size, align) in layouts {
+            record.extend_from_slice(&size.to_le_bytes());
+            record.extend_from_slice(&align.to_le_bytes());
+        }
+        record
+    }
+
+    const TRANSFER: &str =
+        r#"{"name":"transfer","kind":"transaction","args":["u64","[u8;32]"],"returns":"bool"}"#;
+    const BALANCE: &str = r#"{"name":"balance","kind":"query","args":[],"returns":"u64"}"#;
+
+    fn ty(name: &str, size: u32, align: u32) -> TypeAbi {
+        TypeAbi {
+            name: name.into(),
+            size,
+            align,
+        }
+    }
+
+    #[test]
+    fn reads_records() {
+        let mut section = record(TRANSFER, &[(8, 8), (32, 1), (1, 1)]);
+        section.extend(record(BALANCE, &[(8, 8)]));
+
+        let abi = read(&module(&section)).expect("The ABI should be read");
+
+        assert_eq!(
+            abi.functions(),
+            &[
+                FunctionAbi {
+                    name: "transfer".into(),
+                    kind: FunctionKind::Transaction,
+                    args: vec![ty("u64", 8, 8), ty("[u8;32]", 32, 1)],
+                    returns: ty("bool", 1, 1),
+                },
+                FunctionAbi {
+                    name: "balance".into(),
+                    kind: FunctionKind::Query,
+                    args: vec![],
+                    returns: ty("u64", 8, 8),
+                },
+            ]
+        );
+        assert_eq!(abi.function("balance"), Some(&abi.functions()[1]));
+        assert_eq!(abi.function("mint"), None);
+    }
+
+    #[test]
+    fn empty_without_section() {
+        let abi = read(b"\0asm\x01\0\0\0").expect("The module is valid");
+        assert_eq!(abi, ContractAbi::default());
+    }
+
+    #[test]
+    fn truncated_records() {
+        let section = record(TRANSFER, &[(8, 8), (32, 1), (1, 1)]);
+
+        // cut in the length, the JSON and the layouts
+        for len in [2, 20, section.len() - 4] {
+            assert_eq!(
+                read(&module(&section[..len])),
+                Err(VMError::InvalidData),
+                "record cut at {}",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn malformed_records() {
+        let cases = [
+            record("{not json", &[]),
+            record(
+                r#"{"name":"f","kind":"constructor","args":[],"returns":"()"}"#,
+                &[(0, 1)],
+            ),
+            record(r#"{"name":"f","kind":"query","args":[]}"#, &[(0, 1)]),
+            // a JSON length past the end of the section
+            u32::MAX.to_le_bytes().to_vec(),
+        ];
+
+        for section in &cases {
+            assert_eq!(read(&module(section)), Err(VMError::InvalidData));
+        }
+    }
+
+    #[test]
+    fn invalid_module() {
+        assert_eq!(read(b"\0asm\x02\0\0\0"), Err(VMError::InvalidWASMModule));
+    }
+}
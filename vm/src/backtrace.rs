@@ -0,0 +1,366 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Guest call stacks captured when a contract traps or panics.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use addr2line::gimli::{Dwarf, EndianSlice, LittleEndian, SectionId};
+use bytecheck::CheckBytes;
+use rkyv::Archive;
+use serde::{Deserialize, Serialize};
+use wasmer::FrameInfo;
+use wasmparser::{Name, NameSectionReader, Parser, Payload};
+
+/// Name of the custom section holding the function names of a module.
+const NAME_SECTION: &str = "name";
+
+/// A position in the source of a contract, from its DWARF debug info.
+#[derive(
+    Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Archive, rkyv::Serialize, rkyv::Deserialize,
+)]
+#[archive_attr(derive(CheckBytes))]
+pub struct SourceLocation {
+    file: String,
+    line: Option<u32>,
+    column: Option<u32>,
+}
+
+impl SourceLocation {
+    /// Returns the path of the source file.
+    pub fn file(&self) -> &str {
+        &self.file
+    }
+
+    /// Returns the line in the source file, if known.
+    pub fn line(&self) -> Option<u32> {
+        self.line
+    }
+
+    /// Returns the column in the line, if known.
+    pub fn column(&self) -> Option<u32> {
+        self.column
+    }
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.file)?;
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+            if let Some(column) = self.column {
+                write!(f, ":{}", column)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A guest function on the call stack of a trap.
+#[derive(
+    Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Archive, rkyv::Serialize, rkyv::Deserialize,
+)]
+#[archive_attr(derive(CheckBytes))]
+pub struct BacktraceFrame {
+    function_index: u32,
+    function_name: Option<String>,
+    module_offset: usize,
+    location: Option<SourceLocation>,
+}
+
+impl BacktraceFrame {
+    fn new(frame: &FrameInfo) -> Self {
+        BacktraceFrame {
+            function_index: frame.func_index(),
+            function_name: frame.function_name().map(demangle),
+            module_offset: frame.module_offset(),
+            location: None,
+        }
+    }
+
+    /// Returns the index of the function in the module.
+    pub fn function_index(&self) -> u32 {
+        self.function_index
+    }
+
+    /// Returns the demangled name of the function, if the module names it.
+    pub fn function_name(&self) -> Option<&str> {
+        self.function_name.as_deref()
+    }
+
+    /// Returns the offset in the module of the instruction executing.
+    pub fn module_offset(&self) -> usize {
+        self.module_offset
+    }
+
+    /// Returns the position in the source of the instruction executing, if
+    /// the module has debug info.
+    pub fn location(&self) -> Option<&SourceLocation> {
+        self.location.as_ref()
+    }
+}
+
+impl fmt::Display for BacktraceFrame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.function_name {
+            Some(name) => write!(f, "{}", name)?,
+            None => write!(f, "<function {}>", self.function_index)?,
+        }
+        write!(f, " @ {:#x}", self.module_offset)?;
+        if let Some(location) = &self.location {
+            write!(f, "\n             at {}", location)?;
+        }
+        Ok(())
+    }
+}
+
+/// The guest functions on the call stack of a trap or panic, innermost
+/// first.
+#[derive(
+    Debug,
+    Clone,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    Archive,
+    rkyv::Serialize,
+    rkyv::Deserialize,
+)]
+#[archive_attr(derive(CheckBytes))]
+pub struct Backtrace {
+    frames: Vec<BacktraceFrame>,
+}
+
+impl Backtrace {
+    pub(crate) fn new(frames: &[FrameInfo]) -> Self {
+        Backtrace {
+            frames: frames.iter().map(BacktraceFrame::new).collect(),
+        }
+    }
+
+    /// Returns the frames, innermost first.
+    pub fn frames(&self) -> &[BacktraceFrame] {
+        &self.frames
+    }
+
+    /// Names the frames wasmer left unnamed from the name section of
+    /// `code`, and locates them in the source with its DWARF debug info.
+    pub(crate) fn symbolicate(&mut self, code: &[u8]) {
+        if self.frames.is_empty() {
+            return;
+        }
+
+        let symbols = match Symbols::parse(code) {
+            Some(symbols) => symbols,
+            None => return,
+        };
+
+        let context = symbols.dwarf_context();
+
+        for frame in &mut self.frames {
+            if frame.function_name.is_none() {
+                frame.function_name = symbols
+                    .names
+                    .get(&frame.function_index)
+                    .copied()
+                    .map(demangle);
+            }
+            if let Some(context) = &context {
+                frame.location = symbols.locate(context, frame.module_offset);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+impl Backtrace {
+    /// A backtrace of a named frame located in the source, and of an unnamed
+    /// one.
+    pub(crate) fn example() -> Self {
+        Backtrace {
+            frames: vec![
+                BacktraceFrame {
+                    function_index: 3,
+                    function_name: Some("compile::invoke".to_string()),
+                    module_offset: 0x1f4,
+                    location: Some(SourceLocation {
+                        file: "src/lib.rs".to_string(),
+                        line: Some(12),
+                        column: Some(5),
+                    }),
+                },
+                BacktraceFrame {
+                    function_index: 7,
+                    function_name: None,
+                    module_offset: 0x2a0,
+                    location: None,
+                },
+            ],
+        }
+    }
+}
+
+impl fmt::Display for Backtrace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, frame) in self.frames.iter().enumerate() {
+            write!(f, "\n  {:>4}: {}", i, frame)?;
+        }
+        Ok(())
+    }
+}
+
+type DwarfContext<'a> = addr2line::Context<EndianSlice<'a, LittleEndian>>;
+
+/// The symbols of a module, borrowed from its code.
+struct Symbols<'a> {
+    names: BTreeMap<u32, &'a str>,
+    code_section_start: usize,
+    debug_sections: BTreeMap<&'a str, &'a [u8]>,
+}
+
+impl<'a> Symbols<'a> {
+    /// Reads the function names and debug sections of a module, or `None`
+    /// if it is invalid.
+    fn parse(code: &'a [u8]) -> Option<Self> {
+        let mut symbols = Symbols {
+            names: BTreeMap::new(),
+            code_section_start: 0,
+            debug_sections: BTreeMap::new(),
+        };
+
+        for payload in Parser::new(0).parse_all(code) {
+            match payload.ok()? {
+                Payload::CodeSectionStart { range, .. } => {
+                    symbols.code_section_start = range.start;
+                }
+                Payload::CustomSection {
+                    name: NAME_SECTION,
+                    data,
+                    data_offset,
+                    ..
+                } => symbols.read_names(data, data_offset),
+                Payload::CustomSection { name, data, .. } if name.starts_with(".debug_") => {
+                    symbols.debug_sections.insert(name, data);
+                }
+                _ => {}
+            }
+        }
+
+        Some(symbols)
+    }
+
+    /// Reads the function names of the name section, ignoring the invalid
+    /// parts.
+    fn read_names(&mut self, data: &'a [u8], data_offset: usize) {
+        let mut reader = match NameSectionReader::new(data, data_offset) {
+            Ok(reader) => reader,
+            Err(_) => return,
+        };
+
+        while !reader.eof() {
+            let map = match reader.read() {
+                Ok(Name::Function(names)) => names.get_map(),
+                Ok(_) => continue,
+                Err(_) => return,
+            };
+            let mut map = match map {
+                Ok(map) => map,
+                Err(_) => return,
+            };
+
+            for _ in 0..map.get_count() {
+                match map.read() {
+                    Ok(naming) => {
+                        self.names.insert(naming.index, naming.name);
+                    }
+                    Err(_) => return,
+                }
+            }
+        }
+    }
+
+    /// Returns the context to look up source locations in, if the module
+    /// has DWARF debug info.
+    fn dwarf_context(&self) -> Option<DwarfContext<'a>> {
+        if self.debug_sections.is_empty() {
+            return None;
+        }
+
+        let dwarf = Dwarf::load(|id: SectionId| -> Result<_, ()> {
+            let data = self.debug_sections.get(id.name()).copied().unwrap_or(&[]);
+            Ok(EndianSlice::new(data, LittleEndian))
+        })
+        .ok()?;
+        addr2line::Context::from_dwarf(dwarf).ok()
+    }
+
+    /// Returns the source location of the instruction at `module_offset`,
+    /// if there is DWARF line info for it. Code addresses in wasm DWARF are
+    /// relative to the start of the code section.
+    fn locate(&self, context: &DwarfContext<'a>, module_offset: usize) -> Option<SourceLocation> {
+        if module_offset < self.code_section_start {
+            return None;
+        }
+
+        let address = (module_offset - self.code_section_start) as u64;
+        let location = context.find_location(address).ok()??;
+
+        Some(SourceLocation {
+            file: location.file?.to_string(),
+            line: location.line,
+            column: location.column,
+        })
+    }
+}
+
+/// Demangles a Rust symbol, without its hash.
+fn demangle(name: &str) -> String {
+    format!("{:#}", rustc_demangle::demangle(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unnamed(function_index: u32) -> BacktraceFrame {
+        BacktraceFrame {
+            function_index,
+            function_name: None,
+            module_offset: 0,
+            location: None,
+        }
+    }
+
+    #[test]
+    fn names_frames_from_name_section() {
+        let code = wat::parse_str("(module (func $first) (func) (func $third))").unwrap();
+
+        let mut backtrace = Backtrace {
+            frames: vec![unnamed(2), unnamed(1), unnamed(0)],
+        };
+        backtrace.symbolicate(&code);
+
+        let names: Vec<_> = backtrace
+            .frames()
+            .iter()
+            .map(BacktraceFrame::function_name)
+            .collect();
+        assert_eq!(names, vec![Some("third"), None, Some("first")]);
+    }
+
+    #[test]
+    fn invalid_code_leaves_frames_unnamed() {
+        let mut backtrace = Backtrace {
+            frames: vec![unnamed(0)],
+        };
+        backtrace.symbolicate(b"not wasm");
+
+        assert_eq!(backtrace.frames()[0].function_name(), None);
+    }
+}
@@ -6,25 +6,39 @@
 
 #![allow(dead_code)]
 
-use primitives::ReturnValue;
+use std::collections::BTreeMap;
+
+use bytecheck::CheckBytes;
+use primitives::native::Hasher;
+use primitives::{ContractId, LogLevel, ReturnValue};
+use rkyv::validation::validators::DefaultValidator;
+use rkyv::{Archive, Deserialize, Infallible};
 
 use tracing::{trace, trace_span};
-use wasmer::{Exports, ImportObject, Instance, LazyInit, Module, NativeFunc};
+use wasmer::{Exports, ImportObject, Instance, LazyInit, Module, NativeFunc, RuntimeError, Val};
 use wasmer_middlewares::metering::set_remaining_points;
 
 use crate::compiler::WasmerCompiler;
 use crate::env::Env;
 use crate::gas::{Gas, GasMeter};
-use crate::memory::WasmerMemory;
-use crate::resolver::HostImportsResolver;
+use crate::memory::{GuestPtr, GuestSlice, Pod, WasmerMemory};
+use crate::receipt::{Event, LogRecord, Receipt};
 use crate::state::Vm;
 use crate::{Config, VMError};
 
+/// Name of the global a contract exports the address of its scratch buffer
+/// under.
+const SCRATCH: &str = "SCRATCH";
+
 pub struct StackFrame {
+    call: usize,
     ret: ReturnValue,
     memory: WasmerMemory,
     gas_meter: GasMeter,
     instance: Instance,
+    events: Vec<Event>,
+    hashers: Vec<Option<Hasher>>,
+    states: BTreeMap<ContractId, Vec<u8>>,
 }
 
 impl std::fmt::Debug for StackFrame {
@@ -34,12 +48,21 @@ impl std::fmt::Debug for StackFrame {
 }
 
 impl StackFrame {
-    fn new(memory: WasmerMemory, gas_meter: GasMeter, instance: Instance) -> StackFrame {
+    fn new(
+        call: usize,
+        memory: WasmerMemory,
+        gas_meter: GasMeter,
+        instance: Instance,
+    ) -> StackFrame {
         StackFrame {
+            call,
             memory,
             ret: Default::default(),
             gas_meter,
             instance,
+            events: vec![],
+            hashers: vec![],
+            states: BTreeMap::new(),
         }
     }
 
@@ -52,41 +75,142 @@ impl StackFrame {
     }
 }
 
+/// The context host functions are executed in, giving access to the gas meter
+/// and memory of the calling contract.
 pub struct CallContext<'a> {
     state: &'a mut Vm,
     stack: Vec<StackFrame>,
+    calls: usize,
+    events: Vec<Event>,
+    logs: Vec<LogRecord>,
+    debug_bytes: usize,
+    store: Vec<u8>,
+    states: BTreeMap<ContractId, Vec<u8>>,
 }
 
 impl<'a> CallContext<'a> {
-    pub fn new(state: &'a mut Vm) -> Self {
+    pub(crate) fn new(state: &'a mut Vm) -> Self {
         CallContext {
             state,
             stack: vec![],
+            calls: 0,
+            events: vec![],
+            logs: vec![],
+            debug_bytes: 0,
+            store: vec![],
+            states: BTreeMap::new(),
         }
     }
 
     fn register_namespace(
+        &self,
         namespace_name: &str,
         env: &Env,
         module: &Module,
         import_names: &[String],
         import_object: &mut ImportObject,
-    ) {
+    ) -> Result<(), VMError> {
         let mut namespace = Exports::new();
-        HostImportsResolver::insert_into_namespace(
+        self.state.host_functions().insert_into_namespace(
             &mut namespace,
+            namespace_name,
             module.store(),
             env.clone(),
             import_names,
-        );
+        )?;
         import_object.register(namespace_name, namespace);
+        Ok(())
+    }
+
+    /// Compiles and instantiates a module, with the host functions it
+    /// imports bound to the given environment.
+    fn instantiate(
+        &self,
+        bytecode: &[u8],
+        env: &Env,
+        gas_meter: &GasMeter,
+    ) -> Result<(Instance, WasmerMemory), VMError> {
+        let module = WasmerCompiler::create_module(bytecode, self.state.config())?;
+
+        let mut namespaces: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for import in module.imports() {
+            namespaces
+                .entry(import.module().to_string())
+                .or_default()
+                .push(import.name().to_string());
+        }
+        let mut import_object = ImportObject::new();
+
+        for (namespace_name, import_names) in &namespaces {
+            self.register_namespace(
+                namespace_name,
+                env,
+                &module,
+                import_names,
+                &mut import_object,
+            )?;
+        }
+
+        let instance = Instance::new(&module, &import_object)?;
+        set_remaining_points(&instance, gas_meter.left());
+
+        let mut memory = WasmerMemory {
+            inner: LazyInit::new(),
+        };
+        memory.init(&instance.exports)?;
+
+        Ok((instance, memory))
+    }
+
+    /// Pushes the frame of a new call, numbered in the order calls are made.
+    fn push_frame(&mut self, memory: WasmerMemory, gas_meter: GasMeter, instance: Instance) {
+        let call = self.calls;
+        self.calls += 1;
+        self.stack
+            .push(StackFrame::new(call, memory, gas_meter, instance));
     }
 
-    pub fn execute(
+    /// Pops the topmost stack frame once its call returned, reconciling the
+    /// gas spent and committing its events if it succeeded. The contract
+    /// states it changed are committed too, unless the frame was entered by
+    /// a query. Traps and panics get the backtrace of the guest, symbolicated
+    /// with `bytecode`.
+    fn finish<T>(
+        &mut self,
+        r: Result<T, RuntimeError>,
+        bytecode: &[u8],
+        gas_meter: &mut GasMeter,
+        transact: bool,
+    ) -> Result<(T, StackFrame), VMError> {
+        let reconciliation = self.gas_reconciliation();
+        let mut frame = self.stack.pop().expect("Stack should not be empty");
+
+        match reconciliation {
+            Ok(gas) => *gas_meter = gas,
+            Err(e) => {
+                gas_meter.exhaust();
+                return Err(e);
+            }
+        }
+        trace!(
+            "Finished query with gas limit/spent: {}/{}",
+            gas_meter.limit(),
+            gas_meter.spent()
+        );
+
+        let result = r.map_err(|e| VMError::from(e).symbolicate(bytecode))?;
+        self.commit_events(std::mem::take(&mut frame.events));
+        if transact {
+            self.commit_states(std::mem::take(&mut frame.states));
+        }
+        Ok((result, frame))
+    }
+
+    pub(crate) fn execute(
         &mut self,
         bytecode: &[u8],
         entrypoint: &str,
-        gas_meter: &'a mut GasMeter,
+        gas_meter: &mut GasMeter,
     ) -> Result<(), VMError> {
         let _span = trace_span!(
             "query",
@@ -95,63 +219,269 @@ impl<'a> CallContext<'a> {
         );
 
         let env = Env::new(self);
+        let (instance, memory) = self.instantiate(bytecode, &env, gas_meter)?;
 
-        let instance: Instance;
+        let run_func: NativeFunc<(), ()> = instance.exports.get_native_function(entrypoint)?;
 
-        let r = {
-            let module = WasmerCompiler::create_module(bytecode, self.state.config())?;
+        self.push_frame(memory, gas_meter.clone(), instance);
 
-            let import_names: Vec<String> =
-                module.imports().map(|i| i.name().to_string()).collect();
-            let mut import_object = ImportObject::new();
+        let r = run_func.call();
+        self.finish(r, bytecode, gas_meter, true)?;
+        Ok(())
+    }
 
-            CallContext::register_namespace(
-                "env",
-                &env,
-                &module,
-                &import_names,
-                &mut import_object,
-            );
+    /// Calls an entrypoint generated by `#[bindgen]` for a function with
+    /// arguments or a return value. The archived state and argument are
+    /// passed through the `SCRATCH` buffer of the contract, which the return
+    /// value is read back from. Contract states changed in a call that does
+    /// not `transact` are discarded with its frame.
+    pub(crate) fn call(
+        &mut self,
+        bytecode: &[u8],
+        entrypoint: &str,
+        state: &[u8],
+        arg: &[u8],
+        gas_meter: &mut GasMeter,
+        transact: bool,
+    ) -> Result<ReturnValue, VMError> {
+        let _span = trace_span!(
+            "call",
+            gas_limit = ?gas_meter.limit(),
+            stack_index = ?self.stack.len()
+        );
 
-            instance = Instance::new(&module, &import_object)?;
-            set_remaining_points(&instance, gas_meter.left());
+        if state.len() + arg.len() > primitives::BUFFER_SIZE_LIMIT {
+            trace!("State and argument do not fit the scratch buffer");
+            return Err(VMError::InvalidData);
+        }
 
-            let mut memory = WasmerMemory {
-                inner: LazyInit::new(),
-            };
-            memory.init(&instance.exports)?;
+        let env = Env::new(self);
+        let (instance, memory) = self.instantiate(bytecode, &env, gas_meter)?;
 
-            self.stack
-                .push(StackFrame::new(memory, gas_meter.clone(), instance.clone()));
+        let scratch = match instance.exports.get_global(SCRATCH)?.get() {
+            Val::I32(offset) => offset as u32 as u64,
+            _ => return Err(VMError::InvalidData),
+        };
+        memory.write(scratch, state)?;
+        memory.write(scratch + state.len() as u64, arg)?;
 
-            let run_func: NativeFunc<(), ()> = instance.exports.get_native_function(entrypoint)?;
+        let call_func: NativeFunc<(u32, u32), u64> =
+            instance.exports.get_native_function(entrypoint)?;
 
-            let mut memory = WasmerMemory::new();
-            memory.init(&instance.exports)?;
+        self.push_frame(memory, gas_meter.clone(), instance);
 
-            let r = run_func.call();
+        let written_state = state.len() as u32;
+        let written_data = written_state + arg.len() as u32;
+        let r = call_func.call(written_state, written_data);
+        let (lengths, frame) = self.finish(r, bytecode, gas_meter, transact)?;
 
-            r
-        };
+        if lengths == primitives::RETURN_TOO_LARGE_CODE {
+            trace!("Return value does not fit the scratch buffer");
+            return Err(VMError::InvalidData);
+        }
 
-        match self.gas_reconciliation() {
-            Ok(gas) => *gas_meter = gas,
-            Err(e) => {
-                gas_meter.exhaust();
-                return Err(e);
-            }
+        let state_len = (lengths & 0xffff_ffff) as usize;
+        let total_len = (lengths >> 32) as usize;
+        if state_len > total_len {
+            return Err(VMError::InvalidData);
         }
-        trace!(
-            "Finished query with gas limit/spent: {}/{}",
-            gas_meter.limit(),
-            gas_meter.spent()
-        );
+        let bytes = frame.memory.read(scratch, total_len)?;
+
+        Ok(ReturnValue::with_state(
+            &bytes[state_len..],
+            &bytes[..state_len],
+        ))
+    }
+
+    /// Calls a function of a deployed contract in a nested stack frame,
+    /// with at most `gas_limit` of the gas left. The new state of the callee
+    /// of a transaction is kept in the frame of the caller, while a query
+    /// changes no state at all, even through the transactions it makes.
+    pub(crate) fn call_contract(
+        &mut self,
+        target: ContractId,
+        name: &str,
+        arg: &[u8],
+        gas_limit: u64,
+        transact: bool,
+    ) -> Result<ReturnValue, VMError> {
+        let code = self
+            .state
+            .contract_code(&target)
+            .ok_or(VMError::UnknownContract)?;
+        let state = self
+            .contract_state(&target)
+            .ok_or(VMError::UnknownContract)?;
+
+        let mut gas_meter = self.gas_meter()?.limited(gas_limit);
+        let entrypoint = format!("__vm_{}", name);
+
+        let ret = self.call(&code, &entrypoint, &state, arg, &mut gas_meter, transact)?;
+        if transact {
+            self.top_mut().states.insert(target, ret.state().to_vec());
+        }
+        Ok(ret)
+    }
+
+    /// Returns the current state of a deployed contract, including changes
+    /// made by the frames on the stack.
+    fn contract_state(&self, id: &ContractId) -> Option<Vec<u8>> {
+        self.stack
+            .iter()
+            .rev()
+            .map(|frame| &frame.states)
+            .chain(std::iter::once(&self.states))
+            .find_map(|states| states.get(id).cloned())
+            .or_else(|| self.state.contract_state(id))
+    }
+
+    /// Hands the contract states changed by a successfully exited frame over
+    /// to its caller, or to the execution if it was the outermost one.
+    fn commit_states(&mut self, states: BTreeMap<ContractId, Vec<u8>>) {
+        match self.stack.last_mut() {
+            Some(parent) => parent.states.extend(states),
+            None => self.states.extend(states),
+        }
+    }
+
+    /// Takes the contract states changed by the execution, to be persisted
+    /// once it succeeded.
+    pub(crate) fn take_states(&mut self) -> BTreeMap<ContractId, Vec<u8>> {
+        std::mem::take(&mut self.states)
+    }
+
+    /// Stores bytes from the memory of the topmost stack frame, returning
+    /// the offset they can be retrieved by. The store outlives the frame,
+    /// for the duration of the execution.
+    pub(crate) fn store_put(&mut self, slice: GuestSlice<u8>) -> Result<u64, VMError> {
+        let bytes = self
+            .stack
+            .last()
+            .expect("Stack should not be empty")
+            .memory
+            .read_bytes(slice)?;
+
+        // guests pack the high bits of 32 bit lengths above the offset
+        let offset = self.store.len() as u64;
+        if offset >= 1 << 48 {
+            return Err(VMError::InvalidData);
+        }
+        self.store.extend_from_slice(bytes);
+        Ok(offset)
+    }
+
+    /// Copies stored bytes into the memory of the topmost stack frame.
+    pub(crate) fn store_get(&mut self, offset: u64, buffer: GuestSlice<u8>) -> Result<(), VMError> {
+        let start = offset as usize;
+        let bytes = start
+            .checked_add(buffer.len())
+            .and_then(|end| self.store.get(start..end))
+            .ok_or(VMError::InvalidData)?;
+
+        self.stack
+            .last()
+            .expect("Stack should not be empty")
+            .memory
+            .write_bytes(buffer, bytes)
+    }
+
+    /// Hands the events of a successfully exited frame over to its caller, or
+    /// to the receipt if it was the outermost one.
+    fn commit_events(&mut self, events: Vec<Event>) {
+        match self.stack.last_mut() {
+            Some(parent) => parent.events.extend(events),
+            None => self.events.extend(events),
+        }
+    }
+
+    /// Record an event emitted by the contract in the topmost stack frame.
+    /// The event is discarded if the frame fails.
+    pub fn emit(&mut self, topic: String, data: Vec<u8>) {
+        let frame = self.top_mut();
+        frame.events.push(Event::new(frame.call, topic, data));
+    }
+
+    /// Hand a hasher over to the topmost stack frame, returning the handle
+    /// the guest refers to it by. The hasher is dropped when the frame exits.
+    ///
+    /// Each handle is charged for, and a frame can have at most
+    /// [`Config::max_open_hashers`] open at once.
+    pub(crate) fn insert_hasher(&mut self, hasher: Hasher) -> Result<i32, VMError> {
+        let config = self.config();
+        self.charge_gas(config.host_costs.hash_handle)?;
+
+        let hashers = &mut self.top_mut().hashers;
+        let open = hashers.iter().filter(|hasher| hasher.is_some()).count();
+        if open >= config.max_open_hashers as usize {
+            return Err(VMError::TooManyHandles);
+        }
+
+        hashers.push(Some(hasher));
+        Ok((hashers.len() - 1) as i32)
+    }
+
+    /// Feed a slice of the memory of the topmost stack frame into one of its
+    /// hashers.
+    pub(crate) fn update_hasher(
+        &mut self,
+        handle: i32,
+        input: GuestSlice<u8>,
+    ) -> Result<(), VMError> {
+        let frame = self.top_mut();
+        let bytes = frame.memory.read_bytes(input)?;
+        let hasher = frame
+            .hashers
+            .get_mut(handle as usize)
+            .and_then(Option::as_mut)
+            .ok_or(VMError::InvalidHandle)?;
+
+        hasher.update(bytes);
+        Ok(())
+    }
+
+    /// Take a hasher back from the topmost stack frame, invalidating its
+    /// handle.
+    pub(crate) fn take_hasher(&mut self, handle: i32) -> Result<Hasher, VMError> {
+        self.top_mut()
+            .hashers
+            .get_mut(handle as usize)
+            .and_then(Option::take)
+            .ok_or(VMError::InvalidHandle)
+    }
+
+    /// Record a log message and send it to the debug sink of the VM, unless
+    /// its level is below [`Config::min_log_level`] or the debug output of
+    /// the execution exceeds [`Config::max_debug_bytes`].
+    pub fn log(&mut self, level: LogLevel, message: &str) {
+        if !self.accepts_log(level, message.len()) {
+            trace!("Dropping debug message, below the log level or output limit reached");
+            return;
+        }
+        self.debug_bytes += message.len();
+
+        self.state.debug_sink().debug(level, message);
+        self.logs.push(LogRecord::new(level, message.to_string()));
+    }
+
+    /// Returns whether a log message of `level` and `len` bytes would be
+    /// kept by [`log`](Self::log), so dropped ones need not be read.
+    pub fn accepts_log(&self, level: LogLevel, len: usize) -> bool {
+        level >= self.config().min_log_level
+            && self.debug_bytes + len <= self.config().max_debug_bytes as usize
+    }
+
+    /// Takes the messages logged during the execution, for the receipt of
+    /// its failure.
+    pub(crate) fn take_logs(&mut self) -> Vec<LogRecord> {
+        std::mem::take(&mut self.logs)
+    }
 
-        let result = r.map_err(|a| VMError::ExecutionPanic(a.message()))?;
-        self.stack.pop();
-        Ok(result)
+    /// Consumes the context, returning the receipt of the execution.
+    pub(crate) fn into_receipt(self) -> Receipt {
+        Receipt::new(self.events, self.logs)
     }
 
+    /// Returns the up to date gas meter of the topmost stack frame.
     pub fn gas_meter(&mut self) -> Result<&GasMeter, VMError> {
         let stack = &mut self.top_mut();
         let instance = &stack.instance;
@@ -173,34 +503,87 @@ impl<'a> CallContext<'a> {
         Ok(())
     }
 
+    /// Returns the configuration of the VM.
     pub fn config(&self) -> &'static Config {
         self.state.config()
     }
 
-    pub fn top(&self) -> &StackFrame {
+    pub(crate) fn top(&self) -> &StackFrame {
         self.stack.last().expect("Stack should not be empty")
     }
 
-    pub fn top_mut(&mut self) -> &mut StackFrame {
+    pub(crate) fn top_mut(&mut self) -> &mut StackFrame {
         self.stack.last_mut().expect("Stack should not be empty")
     }
 
+    /// Read bytes from the memory of the topmost stack frame.
     pub fn read_memory(&self, offset: u64, length: usize) -> Result<&[u8], VMError> {
         self.top().read_memory(offset, length)
     }
 
+    /// Write bytes into the memory of the topmost stack frame.
     pub fn write_memory(&mut self, source_slice: &[u8], offset: u64) -> Result<(), VMError> {
         self.top_mut().write_memory(source_slice, offset)?;
         Ok(())
     }
 
-    /// Reconcile the gas usage across the stack.
+    /// Read a value from the memory of the topmost stack frame.
+    pub fn read_pod<T: Pod>(&self, ptr: GuestPtr<T>) -> Result<T, VMError> {
+        self.top().memory.read_pod(ptr)
+    }
+
+    /// Write a value into the memory of the topmost stack frame.
+    pub fn write_pod<T: Pod>(&mut self, ptr: GuestPtr<T>, value: &T) -> Result<(), VMError> {
+        self.top_mut().memory.write_pod(ptr, value)
+    }
+
+    /// Read a byte slice from the memory of the topmost stack frame.
+    pub fn read_bytes(&self, slice: GuestSlice<u8>) -> Result<&[u8], VMError> {
+        self.top().memory.read_bytes(slice)
+    }
+
+    /// Write bytes into a slice of the memory of the topmost stack frame.
+    pub fn write_bytes(&mut self, slice: GuestSlice<u8>, bytes: &[u8]) -> Result<(), VMError> {
+        self.top_mut().memory.write_bytes(slice, bytes)
+    }
+
+    /// Copy a slice of values out of the memory of the topmost stack frame.
+    pub fn read_slice<T: Pod>(&self, slice: GuestSlice<T>) -> Result<Vec<T>, VMError> {
+        self.top().memory.read_slice(slice)
+    }
+
+    /// Read a UTF-8 string from the memory of the topmost stack frame.
+    pub fn read_str(&self, slice: GuestSlice<u8>) -> Result<&str, VMError> {
+        self.top().memory.read_str(slice)
+    }
+
+    /// Validate and deserialize an archived value from the memory of the
+    /// topmost stack frame.
+    pub fn read_archived<T>(&self, slice: GuestSlice<u8>) -> Result<T, VMError>
+    where
+        T: Archive,
+        T::Archived: for<'b> CheckBytes<DefaultValidator<'b>> + Deserialize<T, Infallible>,
+    {
+        self.top().memory.read_archived(slice)
+    }
+
+    /// Reconcile the gas usage across the stack, charging the caller of the
+    /// topmost frame for the gas it spent. A callee that ran out of gas
+    /// spent its whole limit.
     fn gas_reconciliation(&mut self) -> Result<GasMeter, VMError> {
+        let frame = self.top_mut();
+        let exhausted = frame.gas_meter.update(&frame.instance, 0).is_err();
+        let gas_meter = frame.gas_meter.clone();
+
         // If there is more than one [`StackFrame`] on the stack, then the
         // gas needs to be reconciled.
         if self.stack.len() > 1 {
             let len = self.stack.len() - 2;
-            let spent = self.gas_meter()?.spent();
+            let spent = if exhausted {
+                gas_meter.limit()
+            } else {
+                gas_meter.spent()
+            };
             let parent = &mut self.stack[len];
             let parent_meter = &mut parent.gas_meter;
             let parent_instance = &parent.instance;
@@ -214,6 +597,10 @@ impl<'a> CallContext<'a> {
             // how to handle the gas consumption inside native calls.
             parent_meter.update(parent_instance, spent)?;
         }
-        Ok(self.gas_meter()?.clone())
+
+        if exhausted {
+            return Err(VMError::OutOfGas);
+        }
+        Ok(gas_meter)
     }
 }
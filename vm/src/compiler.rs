@@ -118,6 +118,6 @@ impl WasmerCompiler {
             LimitingTunables::new(base, Pages(config.max_memory_pages), config.max_table_size);
         let store =
             wasmer::Store::new_with_tunables(&Universal::new(compiler_config).engine(), tunables);
-        Module::new(&store, bytecode).map_err(VMError::WasmerCompileError)
+        Module::new(&store, bytecode).map_err(VMError::from)
     }
 }
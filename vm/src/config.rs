@@ -6,6 +6,8 @@
 
 //! Configuration of the virtual machine.
 
+use primitives::LogLevel;
+
 use crate::Gas;
 
 pub static DEFAULT_CONFIG: Config = Config::new();
@@ -25,6 +27,17 @@ pub struct Config {
     /// Is metering on
     pub has_metering: bool,
 
+    /// Maximum number of debug bytes kept per execution, further messages
+    /// are dropped
+    pub max_debug_bytes: u32,
+
+    /// Minimum level of the messages logged by contracts that are kept,
+    /// lower ones are dropped
+    pub min_log_level: LogLevel,
+
+    /// Maximum number of hashers a call can have open at once
+    pub max_open_hashers: u32,
+
     /// Cost per instruction type
     pub op_costs: OpCosts,
 
@@ -40,6 +53,9 @@ impl Config {
             max_table_size: 16384,
             max_memory_pages: 16384,
             has_metering: true,
+            max_debug_bytes: 64 * 1024,
+            min_log_level: LogLevel::Trace,
+            max_open_hashers: 16,
             op_costs: OpCosts::new(),
             host_costs: HostCosts::new(),
         }
@@ -115,20 +131,88 @@ impl Default for OpCosts {
 #[allow(missing_docs)]
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct HostCosts {
+    pub abort: Gas,
+    pub blake2b: Gas,
+    pub blake2b_per_byte: Gas,
+    pub bls_pairing_check: Gas,
+    pub bls_pairing_check_per_pair: Gas,
     pub debug: Gas,
+    pub emit: Gas,
+    pub emit_per_byte: Gas,
     pub gas_consumed: Gas,
     pub gas_left: Gas,
+    pub hash_finalize: Gas,
+    pub hash_handle: Gas,
+    pub hash_init: Gas,
+    pub hash_update: Gas,
+    pub hash_update_per_byte: Gas,
+    pub jubjub_add: Gas,
+    pub jubjub_mul: Gas,
+    pub keccak256: Gas,
+    pub keccak256_per_byte: Gas,
+    pub log: Gas,
+    pub log_per_byte: Gas,
+    pub poseidon_hash: Gas,
+    pub poseidon_hash_per_scalar: Gas,
+    pub query: Gas,
+    pub recover_secp256k1: Gas,
     pub sha256: Gas,
+    pub sha512: Gas,
+    pub sha512_per_byte: Gas,
+    pub store_get: Gas,
+    pub store_get_per_byte: Gas,
+    pub store_put: Gas,
+    pub store_put_per_byte: Gas,
+    pub transact: Gas,
+    pub verify_ed25519: Gas,
+    pub verify_ed25519_batch: Gas,
+    pub verify_ed25519_batch_per_item: Gas,
+    pub verify_ed25519_per_byte: Gas,
+    pub verify_secp256k1: Gas,
 }
 
 impl HostCosts {
     /// Creates a new [`HostCosts`] with default values
     pub const fn new() -> Self {
         Self {
+            abort: 1,
+            blake2b: 100,
+            blake2b_per_byte: 1,
+            bls_pairing_check: 20000,
+            bls_pairing_check_per_pair: 15000,
             debug: 1,
+            emit: 10,
+            emit_per_byte: 1,
             gas_consumed: 1,
             gas_left: 1,
+            hash_finalize: 50,
+            hash_handle: 100,
+            hash_init: 50,
+            hash_update: 10,
+            hash_update_per_byte: 1,
+            jubjub_add: 200,
+            jubjub_mul: 4000,
+            keccak256: 100,
+            keccak256_per_byte: 1,
+            log: 1,
+            log_per_byte: 1,
+            poseidon_hash: 300,
+            poseidon_hash_per_scalar: 200,
+            query: 500,
+            recover_secp256k1: 2000,
             sha256: 100,
+            sha512: 100,
+            sha512_per_byte: 1,
+            store_get: 10,
+            store_get_per_byte: 1,
+            store_put: 10,
+            store_put_per_byte: 1,
+            transact: 500,
+            verify_ed25519: 1000,
+            verify_ed25519_batch: 1000,
+            verify_ed25519_batch_per_item: 400,
+            verify_ed25519_per_byte: 1,
+            verify_secp256k1: 1500,
         }
     }
 }
@@ -4,16 +4,36 @@
 //
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
+// `thiserror` takes any field of a type named `Backtrace` for a
+// `std::backtrace::Backtrace`
+use crate::backtrace::Backtrace as GuestBacktrace;
 use crate::gas;
 use thiserror::Error;
-use wasmer_vm::TrapCode;
+use wasmer_vm::TrapCode as WasmerTrapCode;
 
-#[derive(Error, Debug)]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
 /// The errors that can happen while executing the VM
+///
+/// Errors of wasmer are converted to their message where they enter the VM,
+/// so the shape of this type only changes with the VM itself. Each variant
+/// has a stable numeric [`code`](VMError::code).
 pub enum VMError {
     /// The Contract Panicked
-    #[error("Panicked with message: {0}")]
-    ExecutionPanic(String),
+    #[error("Panicked with message: {message}{backtrace}")]
+    ExecutionPanic {
+        /// Message of the panic
+        message: String,
+        /// Guest functions on the call stack when the contract panicked
+        backtrace: GuestBacktrace,
+    },
+    /// The contract aborted its execution
+    #[error("Contract aborted with code {code}: {message}")]
+    ContractAbort {
+        /// Error code given by the contract
+        code: i32,
+        /// Message given by the contract
+        message: String,
+    },
     /// Instrumentation Error
     #[error(transparent)]
     InstrumentationError(#[from] InstrumentationError),
@@ -23,6 +43,18 @@ pub enum VMError {
     /// Error from reading invalid data
     #[error("Invalid data")]
     InvalidData,
+    /// Guest memory access out of bounds
+    #[error("Memory access out of bounds")]
+    MemoryAccessOutOfBounds,
+    /// No contract deployed under the called id
+    #[error("Unknown contract")]
+    UnknownContract,
+    /// Handle not referring to any resource of the contract
+    #[error("Invalid handle")]
+    InvalidHandle,
+    /// The contract holds as many handles as it may
+    #[error("Too many open handles")]
+    TooManyHandles,
     /// Contract execution ran out of gas
     #[error("Contract execution ran out of gas")]
     OutOfGas,
@@ -30,23 +62,86 @@ pub enum VMError {
     #[error("Invalid WASM module")]
     InvalidWASMModule,
     /// WASMER export error
-    #[error(transparent)]
-    WasmerExportError(#[from] wasmer::ExportError),
-    /// WASMER runtime error
-    #[error(transparent)]
-    WasmerRuntimeError(wasmer::RuntimeError),
+    #[error("{0}")]
+    WasmerExportError(String),
     /// WASMER  compile error
-    #[error(transparent)]
-    WasmerCompileError(#[from] wasmer::CompileError),
+    #[error("{0}")]
+    WasmerCompileError(String),
     /// WASMER instantiation error
-    #[error(transparent)]
-    WasmerInstantiationError(#[from] wasmer::InstantiationError),
-    /// WASMER trap
-    #[error("WASMER trap")]
-    WasmerTrap(TrapCode),
+    #[error("{0}")]
+    WasmerInstantiationError(String),
+    /// The contract trapped
+    #[error("WASMER trap: {code}{backtrace}")]
+    WasmerTrap {
+        /// Reason of the trap
+        code: TrapCode,
+        /// Guest functions on the call stack when the contract trapped
+        backtrace: GuestBacktrace,
+    },
 }
 
-#[derive(Error, Debug)]
+/// The reason a contract trapped, independent of the version of wasmer the
+/// VM is built with.
+#[derive(Error, Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum TrapCode {
+    /// The call stack was exhausted
+    #[error("call stack exhausted")]
+    StackOverflow,
+    /// Memory was accessed out of its bounds
+    #[error("out of bounds memory access")]
+    HeapAccessOutOfBounds,
+    /// Memory was accessed at a misaligned address
+    #[error("misaligned heap")]
+    HeapMisaligned,
+    /// A table was accessed out of its bounds
+    #[error("undefined element: out of bounds table access")]
+    TableAccessOutOfBounds,
+    /// Another bounds check failed
+    #[error("out of bounds")]
+    OutOfBounds,
+    /// An uninitialized table element was called
+    #[error("uninitialized element")]
+    IndirectCallToNull,
+    /// An indirect call had the wrong signature
+    #[error("indirect call type mismatch")]
+    BadSignature,
+    /// An integer operation overflowed
+    #[error("integer overflow")]
+    IntegerOverflow,
+    /// An integer was divided by zero
+    #[error("integer divide by zero")]
+    IntegerDivisionByZero,
+    /// A float could not be converted to an integer
+    #[error("invalid conversion to integer")]
+    BadConversionToInteger,
+    /// An `unreachable` instruction was executed
+    #[error("unreachable")]
+    UnreachableCodeReached,
+    /// An atomic memory access was misaligned
+    #[error("unaligned atomic access")]
+    UnalignedAtomic,
+}
+
+impl TrapCode {
+    fn from_wasmer(code: WasmerTrapCode) -> Self {
+        match code {
+            WasmerTrapCode::StackOverflow => TrapCode::StackOverflow,
+            WasmerTrapCode::HeapAccessOutOfBounds => TrapCode::HeapAccessOutOfBounds,
+            WasmerTrapCode::HeapMisaligned => TrapCode::HeapMisaligned,
+            WasmerTrapCode::TableAccessOutOfBounds => TrapCode::TableAccessOutOfBounds,
+            WasmerTrapCode::OutOfBounds => TrapCode::OutOfBounds,
+            WasmerTrapCode::IndirectCallToNull => TrapCode::IndirectCallToNull,
+            WasmerTrapCode::BadSignature => TrapCode::BadSignature,
+            WasmerTrapCode::IntegerOverflow => TrapCode::IntegerOverflow,
+            WasmerTrapCode::IntegerDivisionByZero => TrapCode::IntegerDivisionByZero,
+            WasmerTrapCode::BadConversionToInteger => TrapCode::BadConversionToInteger,
+            WasmerTrapCode::UnreachableCodeReached => TrapCode::UnreachableCodeReached,
+            WasmerTrapCode::UnalignedAtomic => TrapCode::UnalignedAtomic,
+        }
+    }
+}
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum InstrumentationError {
     #[error("gas metering injection")]
     GasMeteringInjection,
@@ -80,8 +175,79 @@ impl From<wasmer::RuntimeError> for VMError {
         };
 
         match e.clone().to_trap() {
-            Some(trap_code) => VMError::WasmerTrap(trap_code),
-            None => VMError::WasmerRuntimeError(e),
+            Some(code) => VMError::WasmerTrap {
+                code: TrapCode::from_wasmer(code),
+                backtrace: GuestBacktrace::new(e.trace()),
+            },
+            None => VMError::ExecutionPanic {
+                message: e.message(),
+                backtrace: GuestBacktrace::new(e.trace()),
+            },
+        }
+    }
+}
+
+impl From<wasmer::ExportError> for VMError {
+    fn from(e: wasmer::ExportError) -> Self {
+        VMError::WasmerExportError(e.to_string())
+    }
+}
+
+impl From<wasmer::CompileError> for VMError {
+    fn from(e: wasmer::CompileError) -> Self {
+        VMError::WasmerCompileError(e.to_string())
+    }
+}
+
+impl From<wasmer::InstantiationError> for VMError {
+    fn from(e: wasmer::InstantiationError) -> Self {
+        VMError::WasmerInstantiationError(e.to_string())
+    }
+}
+
+impl VMError {
+    /// Returns the numeric code of the variant, which stays the same across
+    /// versions of the VM. Codes of removed variants are not reused.
+    pub fn code(&self) -> u32 {
+        match self {
+            VMError::ExecutionPanic { .. } => 1,
+            VMError::ContractAbort { .. } => 2,
+            VMError::InstrumentationError(_) => 3,
+            VMError::InvalidUtf8 => 4,
+            VMError::InvalidData => 5,
+            VMError::MemoryAccessOutOfBounds => 6,
+            VMError::UnknownContract => 7,
+            VMError::InvalidHandle => 8,
+            VMError::OutOfGas => 9,
+            VMError::InvalidWASMModule => 10,
+            VMError::WasmerExportError(_) => 11,
+            // 12 was `WasmerRuntimeError`, which runtime errors never became
+            VMError::WasmerCompileError(_) => 13,
+            VMError::WasmerInstantiationError(_) => 14,
+            VMError::WasmerTrap { .. } => 15,
+            VMError::TooManyHandles => 16,
+        }
+    }
+
+    /// Returns the backtrace of the guest, for traps and panics.
+    pub fn backtrace(&self) -> Option<&GuestBacktrace> {
+        match self {
+            VMError::ExecutionPanic { backtrace, .. } | VMError::WasmerTrap { backtrace, .. } => {
+                Some(backtrace)
+            }
+            _ => None,
+        }
+    }
+
+    /// Symbolicates the backtrace of a trap or panic with the symbols of the
+    /// module it happened in.
+    pub(crate) fn symbolicate(mut self, code: &[u8]) -> Self {
+        match &mut self {
+            VMError::ExecutionPanic { backtrace, .. } | VMError::WasmerTrap { backtrace, .. } => {
+                backtrace.symbolicate(code)
+            }
+            _ => {}
         }
+        self
     }
 }
@@ -0,0 +1,226 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Host functions that can be imported by the guest.
+
+use std::sync::Arc;
+
+use wasmer::{Function, FunctionType, RuntimeError, Store, Type, Val};
+
+use crate::call_context::CallContext;
+use crate::env::Env;
+use crate::{Gas, VMError};
+
+/// Namespace host functions are registered in, unless specified otherwise.
+/// Contracts that predate versioned namespaces import the first version of
+/// the host ABI from it.
+pub const DEFAULT_NAMESPACE: &str = "env";
+
+/// Namespace of the first version of the host ABI.
+pub const VM_V1: &str = "vm_v1";
+
+/// Namespace of the second version of the host ABI, in which `sha256` writes
+/// the raw digest instead of its hex encoding.
+pub const VM_V2: &str = "vm_v2";
+
+/// Namespace of the cryptographic host functions.
+pub const CRYPTO: &str = "crypto";
+
+/// A value that can be passed between the guest and a host function.
+pub trait HostValue: Sized {
+    /// The wasm type of the value
+    const TYPE: Type;
+
+    /// Converts a wasm value, if it has the right type.
+    fn from_val(val: &Val) -> Option<Self>;
+
+    /// Converts into a wasm value.
+    fn into_val(self) -> Val;
+}
+
+macro_rules! impl_host_value {
+    ($ty:ty, $variant:ident, $repr:ty) => {
+        impl HostValue for $ty {
+            const TYPE: Type = Type::$variant;
+
+            fn from_val(val: &Val) -> Option<Self> {
+                match val {
+                    Val::$variant(v) => Some(*v as $ty),
+                    _ => None,
+                }
+            }
+
+            fn into_val(self) -> Val {
+                Val::$variant(self as $repr)
+            }
+        }
+    };
+}
+
+impl_host_value!(i32, I32, i32);
+impl_host_value!(u32, I32, i32);
+impl_host_value!(i64, I64, i64);
+impl_host_value!(u64, I64, i64);
+impl_host_value!(f32, F32, f32);
+impl_host_value!(f64, F64, f64);
+
+/// The parameters of a host function, as a tuple of [`HostValue`]s.
+pub trait HostParams: Sized {
+    /// The wasm types of the parameters
+    fn types() -> Vec<Type>;
+
+    /// Converts the wasm values passed by the guest.
+    fn from_vals(vals: &[Val]) -> Result<Self, VMError>;
+}
+
+macro_rules! impl_host_params {
+    ($($param:ident),*) => {
+        impl<$($param: HostValue),*> HostParams for ($($param,)*) {
+            fn types() -> Vec<Type> {
+                vec![$(<$param as HostValue>::TYPE),*]
+            }
+
+            #[allow(unused_variables, unused_mut)]
+            fn from_vals(vals: &[Val]) -> Result<Self, VMError> {
+                let mut vals = vals.iter();
+                Ok(($(
+                    vals.next()
+                        .and_then(<$param as HostValue>::from_val)
+                        .ok_or(VMError::InvalidData)?,
+                )*))
+            }
+        }
+    };
+}
+
+impl_host_params!();
+impl_host_params!(A);
+impl_host_params!(A, B);
+impl_host_params!(A, B, C);
+impl_host_params!(A, B, C, D);
+impl_host_params!(A, B, C, D, E);
+impl_host_params!(A, B, C, D, E, F);
+impl_host_params!(A, B, C, D, E, F, G);
+impl_host_params!(A, B, C, D, E, F, G, H);
+
+/// The results of a host function, either nothing or a single
+/// [`HostValue`].
+pub trait HostResults {
+    /// The wasm types of the results
+    fn types() -> Vec<Type>;
+
+    /// Converts into the wasm values returned to the guest.
+    fn into_vals(self) -> Vec<Val>;
+}
+
+impl HostResults for () {
+    fn types() -> Vec<Type> {
+        vec![]
+    }
+
+    fn into_vals(self) -> Vec<Val> {
+        vec![]
+    }
+}
+
+impl<T: HostValue> HostResults for T {
+    fn types() -> Vec<Type> {
+        vec![T::TYPE]
+    }
+
+    fn into_vals(self) -> Vec<Val> {
+        vec![self.into_val()]
+    }
+}
+
+type HostClosure = dyn Fn(&mut CallContext, &[Val]) -> Result<Vec<Val>, VMError> + Send + Sync;
+
+/// A function provided by the host, that guests can import by its namespace
+/// and name.
+#[derive(Clone)]
+pub struct HostFunction {
+    namespace: String,
+    name: String,
+    signature: FunctionType,
+    cost: Gas,
+    closure: Arc<HostClosure>,
+}
+
+impl std::fmt::Debug for HostFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}::{} {}", self.namespace, self.name, self.signature)
+    }
+}
+
+impl HostFunction {
+    /// Creates a new host function in the [`DEFAULT_NAMESPACE`]. The wasm
+    /// signature is derived from the parameters and results of `closure`.
+    pub fn new<P, R, F>(name: impl Into<String>, closure: F) -> Self
+    where
+        P: HostParams,
+        R: HostResults,
+        F: Fn(&mut CallContext, P) -> Result<R, VMError> + Send + Sync + 'static,
+    {
+        HostFunction {
+            namespace: DEFAULT_NAMESPACE.into(),
+            name: name.into(),
+            signature: FunctionType::new(P::types(), R::types()),
+            cost: 0,
+            closure: Arc::new(move |context, vals| {
+                let params = P::from_vals(vals)?;
+                closure(context, params).map(R::into_vals)
+            }),
+        }
+    }
+
+    /// Sets the namespace the function is imported from.
+    pub fn namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = namespace.into();
+        self
+    }
+
+    /// Sets the gas charged before the function is executed.
+    pub fn cost(mut self, cost: Gas) -> Self {
+        self.cost = cost;
+        self
+    }
+
+    /// Returns the namespace the function is imported from.
+    pub fn namespace_name(&self) -> &str {
+        &self.namespace
+    }
+
+    /// Returns the name the function is imported as.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the wasm signature of the function.
+    pub fn signature(&self) -> &FunctionType {
+        &self.signature
+    }
+
+    pub(crate) fn to_function(&self, store: &Store, env: Env) -> Function {
+        let closure = self.closure.clone();
+        let cost = self.cost;
+
+        Function::new_with_env(
+            store,
+            self.signature.clone(),
+            env,
+            move |env: &Env, vals: &[Val]| -> Result<Vec<Val>, RuntimeError> {
+                let context = env.get_context();
+
+                if cost > 0 {
+                    context
+                        .charge_gas(cost)
+                        .map_err(|e| RuntimeError::user(Box::new(e)))?;
+                }
+                closure(context, vals).map_err(|e| RuntimeError::user(Box::new(e)))
+            },
+        )
+    }
+}
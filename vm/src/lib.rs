@@ -9,6 +9,8 @@
 //! The main engine for executing WASM on the network state
 #![warn(missing_docs)]
 
+pub mod abi;
+mod backtrace;
 mod call_context;
 mod compiler;
 mod compiler_config;
@@ -16,14 +18,26 @@ mod config;
 mod env;
 mod error;
 mod gas;
+mod host_function;
 mod memory;
 mod ops;
+mod receipt;
 mod resolver;
+mod sink;
 mod state;
 
 pub use primitives;
+pub use primitives::LogLevel;
 
+pub use backtrace::{Backtrace, BacktraceFrame, SourceLocation};
+pub use call_context::CallContext;
 pub use config::{Config, HostCosts, OpCosts};
-pub use error::VMError;
+pub use error::{TrapCode, VMError};
 pub use gas::{Gas, GasMeter};
+pub use host_function::{
+    HostFunction, HostParams, HostResults, HostValue, CRYPTO, DEFAULT_NAMESPACE, VM_V1, VM_V2,
+};
+pub use memory::{GuestPtr, GuestSlice, Pod};
+pub use receipt::{ErrorReceipt, Event, LogRecord, Receipt};
+pub use sink::{BufferSink, DebugSink, NoopSink, StdoutSink, TracingSink};
 pub use state::Vm;
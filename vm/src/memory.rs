@@ -6,9 +6,133 @@
 
 #![allow(dead_code)]
 
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::Range;
+
+use bytecheck::CheckBytes;
+use rkyv::validation::validators::DefaultValidator;
+use rkyv::{check_archived_root, AlignedVec, Archive, Deserialize, Infallible};
+
 use crate::VMError;
 use wasmer::{LazyInit, Memory};
 
+/// Values that can be copied in and out of guest memory as raw bytes.
+///
+/// # Safety
+/// Implementors must be valid for any bit pattern and must not contain
+/// padding or pointers.
+pub unsafe trait Pod: Copy + 'static {}
+
+unsafe impl Pod for u8 {}
+unsafe impl Pod for i8 {}
+unsafe impl Pod for u16 {}
+unsafe impl Pod for i16 {}
+unsafe impl Pod for u32 {}
+unsafe impl Pod for i32 {}
+unsafe impl Pod for u64 {}
+unsafe impl Pod for i64 {}
+unsafe impl<T: Pod, const N: usize> Pod for [T; N] {}
+
+/// A pointer to a `T` living in guest memory.
+#[derive(Debug)]
+pub struct GuestPtr<T> {
+    offset: u64,
+    _marker: PhantomData<T>,
+}
+
+impl<T> Clone for GuestPtr<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for GuestPtr<T> {}
+
+impl<T> GuestPtr<T> {
+    /// Creates a pointer from a wasm `i32` address argument.
+    pub fn new(offset: i32) -> Self {
+        GuestPtr {
+            offset: offset as u32 as u64,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the offset of the pointer into guest memory.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+}
+
+/// A slice of `len` consecutive `T`s living in guest memory.
+#[derive(Debug)]
+pub struct GuestSlice<T> {
+    offset: u64,
+    len: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T> Clone for GuestSlice<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for GuestSlice<T> {}
+
+impl<T> GuestSlice<T> {
+    /// Creates a slice from wasm `i32` address and length arguments, failing
+    /// on negative lengths.
+    pub fn new(offset: i32, len: i32) -> Result<Self, VMError> {
+        if len < 0 {
+            return Err(VMError::MemoryAccessOutOfBounds);
+        }
+        Ok(GuestSlice {
+            offset: offset as u32 as u64,
+            len: len as usize,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Returns the offset of the slice into guest memory.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Returns the number of elements in the slice.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the slice has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of bytes spanned by the slice.
+    pub fn byte_len(&self) -> Result<usize, VMError> {
+        self.len
+            .checked_mul(mem::size_of::<T>())
+            .ok_or(VMError::MemoryAccessOutOfBounds)
+    }
+}
+
+/// Validate and deserialize an archived `T`. The bytes are copied first, so
+/// the archive doesn't have to be aligned.
+pub(crate) fn deserialize_archived<T>(bytes: &[u8]) -> Result<T, VMError>
+where
+    T: Archive,
+    T::Archived: for<'b> CheckBytes<DefaultValidator<'b>> + Deserialize<T, Infallible>,
+{
+    let mut aligned = AlignedVec::with_capacity(bytes.len());
+    aligned.extend_from_slice(bytes);
+
+    let archived = check_archived_root::<T>(&aligned).map_err(|_| VMError::InvalidData)?;
+    archived
+        .deserialize(&mut Infallible)
+        .map_err(|_| VMError::InvalidData)
+}
+
 pub struct WasmerMemory {
     pub inner: LazyInit<Memory>,
 }
@@ -27,23 +151,90 @@ impl WasmerMemory {
         Ok(())
     }
 
+    /// Returns the checked byte range for an access at a given offset and
+    /// length
+    fn range(&self, offset: u64, length: usize) -> Result<Range<usize>, VMError> {
+        let size = unsafe { self.inner.get_unchecked().data_size() };
+        let start = offset as usize;
+        let end = start
+            .checked_add(length)
+            .ok_or(VMError::MemoryAccessOutOfBounds)?;
+
+        if end as u64 > size {
+            return Err(VMError::MemoryAccessOutOfBounds);
+        }
+        Ok(start..end)
+    }
+
     /// Read bytes from memory at a given offset and length
     pub fn read(&self, offset: u64, length: usize) -> Result<&[u8], VMError> {
-        let offset = offset as usize;
-        Ok(unsafe { &self.inner.get_unchecked().data_unchecked()[offset..(offset + length)] })
+        let range = self.range(offset, length)?;
+        Ok(unsafe { &self.inner.get_unchecked().data_unchecked()[range] })
     }
 
     /// Write bytes into memory at a given offset
     pub fn write(&self, offset: u64, bytes: impl AsRef<[u8]>) -> Result<(), VMError> {
-        let offset = offset as usize;
         let slice = bytes.as_ref();
+        let range = self.range(offset, slice.len())?;
         unsafe {
-            self.inner.get_unchecked().data_unchecked_mut()[offset..(offset + slice.len())]
-                .copy_from_slice(slice);
+            self.inner.get_unchecked().data_unchecked_mut()[range].copy_from_slice(slice);
         }
         Ok(())
     }
 
+    /// Read a value from memory, regardless of its alignment
+    pub fn read_pod<T: Pod>(&self, ptr: GuestPtr<T>) -> Result<T, VMError> {
+        let bytes = self.read(ptr.offset(), mem::size_of::<T>())?;
+        Ok(unsafe { std::ptr::read_unaligned(bytes.as_ptr() as *const T) })
+    }
+
+    /// Write a value into memory, regardless of its alignment
+    pub fn write_pod<T: Pod>(&self, ptr: GuestPtr<T>, value: &T) -> Result<(), VMError> {
+        let bytes = unsafe {
+            std::slice::from_raw_parts(value as *const T as *const u8, mem::size_of::<T>())
+        };
+        self.write(ptr.offset(), bytes)
+    }
+
+    /// Read the bytes a slice points to
+    pub fn read_bytes(&self, slice: GuestSlice<u8>) -> Result<&[u8], VMError> {
+        self.read(slice.offset(), slice.len())
+    }
+
+    /// Write bytes into a slice, which has to be exactly as long as them
+    pub fn write_bytes(&self, slice: GuestSlice<u8>, bytes: &[u8]) -> Result<(), VMError> {
+        if slice.len() != bytes.len() {
+            return Err(VMError::InvalidData);
+        }
+        self.write(slice.offset(), bytes)
+    }
+
+    /// Copy the elements a slice points to, regardless of their alignment
+    pub fn read_slice<T: Pod>(&self, slice: GuestSlice<T>) -> Result<Vec<T>, VMError> {
+        let bytes = self.read(slice.offset(), slice.byte_len()?)?;
+        Ok(bytes
+            .chunks_exact(mem::size_of::<T>())
+            .map(|chunk| unsafe { std::ptr::read_unaligned(chunk.as_ptr() as *const T) })
+            .collect())
+    }
+
+    /// Read the UTF-8 string a slice points to
+    pub fn read_str(&self, slice: GuestSlice<u8>) -> Result<&str, VMError> {
+        let bytes = self.read_bytes(slice)?;
+        std::str::from_utf8(bytes).map_err(|_| VMError::InvalidUtf8)
+    }
+
+    /// Validate and deserialize an archived `T` from the bytes a slice points
+    /// to. The bytes are copied first, so the archive doesn't have to be
+    /// aligned in guest memory.
+    pub fn read_archived<T>(&self, slice: GuestSlice<u8>) -> Result<T, VMError>
+    where
+        T: Archive,
+        T::Archived: for<'b> CheckBytes<DefaultValidator<'b>> + Deserialize<T, Infallible>,
+    {
+        deserialize_archived(self.read_bytes(slice)?)
+    }
+
     /// Write bytes into memory at a given offset
     pub fn with_mut_slice_from<F, R>(&mut self, offset: usize, closure: F) -> R
     where
@@ -66,3 +257,135 @@ impl WasmerMemory {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use wasmer::{MemoryType, Store};
+
+    const SIZE: u64 = wasmer::WASM_PAGE_SIZE as u64;
+
+    /// A memory of a single wasm page
+    fn memory() -> WasmerMemory {
+        let store = Store::default();
+        let mut memory = WasmerMemory::new();
+        memory
+            .inner
+            .initialize(Memory::new(&store, MemoryType::new(1, None, false)).unwrap());
+        memory
+    }
+
+    fn out_of_bounds<T>(result: Result<T, VMError>) -> bool {
+        matches!(result, Err(VMError::MemoryAccessOutOfBounds))
+    }
+
+    #[test]
+    fn range_checks() {
+        let memory = memory();
+
+        assert_eq!(memory.read(SIZE - 4, 4).unwrap().len(), 4);
+        assert!(memory.read(SIZE, 0).unwrap().is_empty());
+        assert!(out_of_bounds(memory.read(SIZE - 4, 5)));
+        assert!(out_of_bounds(memory.read(SIZE + 1, 0)));
+        assert!(out_of_bounds(memory.read(1, usize::MAX)));
+        assert!(out_of_bounds(memory.write(SIZE - 1, [0u8; 2])));
+    }
+
+    #[test]
+    fn guest_ptr_range_checks() {
+        let memory = memory();
+
+        let last = GuestPtr::<u32>::new(SIZE as i32 - 4);
+        memory.write_pod(last, &7).unwrap();
+        assert_eq!(memory.read_pod(last).unwrap(), 7);
+
+        assert!(out_of_bounds(
+            memory.read_pod(GuestPtr::<u32>::new(SIZE as i32 - 3))
+        ));
+        // negative addresses are read as the unsigned wasm addresses they are
+        assert!(out_of_bounds(memory.read_pod(GuestPtr::<u32>::new(-4))));
+        assert!(out_of_bounds(memory.write_pod(GuestPtr::new(-1), &0u8)));
+    }
+
+    #[test]
+    fn guest_slice_range_checks() {
+        let memory = memory();
+
+        let last = GuestSlice::<[u8; 32]>::new(SIZE as i32 - 64, 2).unwrap();
+        assert_eq!(memory.read_slice(last).unwrap().len(), 2);
+        let past = GuestSlice::<[u8; 32]>::new(SIZE as i32 - 64, 3).unwrap();
+        assert!(out_of_bounds(memory.read_slice(past)));
+
+        let end = GuestSlice::<u8>::new(SIZE as i32, 0).unwrap();
+        assert!(memory.read_bytes(end).unwrap().is_empty());
+
+        assert!(out_of_bounds(GuestSlice::<u8>::new(0, -1)));
+        assert!(out_of_bounds(GuestSlice::<u8>::new(0, i32::MIN)));
+        let negative = GuestSlice::<u8>::new(-1, 1).unwrap();
+        assert_eq!(negative.offset(), u32::MAX as u64);
+        assert!(out_of_bounds(memory.read_bytes(negative)));
+
+        // the longest slice a guest can pass doesn't overflow its byte length
+        let longest = GuestSlice::<[u8; 144]>::new(i32::MAX, i32::MAX).unwrap();
+        assert!(out_of_bounds(memory.read_slice(longest)));
+    }
+
+    #[test]
+    fn unaligned_pod() {
+        let memory = memory();
+        let ptr = GuestPtr::<u64>::new(3);
+
+        memory.write_pod(ptr, &0x0102_0304_0506_0708).unwrap();
+        assert_eq!(memory.read_pod(ptr).unwrap(), 0x0102_0304_0506_0708);
+        assert_eq!(memory.read(3, 8).unwrap(), &[8, 7, 6, 5, 4, 3, 2, 1]);
+
+        let slice = GuestSlice::<u64>::new(3, 1).unwrap();
+        assert_eq!(
+            memory.read_slice(slice).unwrap(),
+            vec![0x0102_0304_0506_0708]
+        );
+    }
+
+    #[test]
+    fn read_str() {
+        let memory = memory();
+        memory.write(0, "hello").unwrap();
+        memory.write(8, [0xff, 0xfe]).unwrap();
+
+        let hello = GuestSlice::new(0, 5).unwrap();
+        assert_eq!(memory.read_str(hello).unwrap(), "hello");
+
+        let invalid = GuestSlice::new(8, 2).unwrap();
+        assert!(matches!(
+            memory.read_str(invalid),
+            Err(VMError::InvalidUtf8)
+        ));
+    }
+
+    #[test]
+    fn read_archived() {
+        let memory = memory();
+        let value: Vec<u32> = (0..16).collect();
+        let bytes = rkyv::to_bytes::<_, 256>(&value).unwrap();
+
+        // at an odd offset, to check the archive is aligned before reading
+        memory.write(1, &bytes).unwrap();
+        let slice = GuestSlice::new(1, bytes.len() as i32).unwrap();
+        assert_eq!(memory.read_archived::<Vec<u32>>(slice).unwrap(), value);
+
+        // the root, a relative pointer and a length, is at the end
+        let root = bytes.len() as u64 + 1 - 8;
+        memory.write(root, [0xff; 8]).unwrap();
+        assert!(matches!(
+            memory.read_archived::<Vec<u32>>(slice),
+            Err(VMError::InvalidData)
+        ));
+
+        let truncated = GuestSlice::new(1, 4).unwrap();
+        assert!(matches!(
+            memory.read_archived::<Vec<u32>>(truncated),
+            Err(VMError::InvalidData)
+        ));
+    }
+}
@@ -0,0 +1,30 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use tracing::trace;
+
+use crate::call_context::CallContext;
+use crate::memory::GuestSlice;
+use crate::VMError;
+
+pub struct Abort;
+
+impl Abort {
+    pub fn abort(
+        context: &mut CallContext,
+        (code, msg_ofs, msg_len): (i32, i32, i32),
+    ) -> Result<(), VMError> {
+        trace!("Executing 'abort' host function");
+
+        let config = context.config();
+        context.charge_gas(config.host_costs.abort)?;
+
+        let message = context.read_bytes(GuestSlice::new(msg_ofs, msg_len)?)?;
+        let message = String::from_utf8_lossy(message).into_owned();
+
+        Err(VMError::ContractAbort { code, message })
+    }
+}
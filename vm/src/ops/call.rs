@@ -0,0 +1,87 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use primitives::{CallError, ContractId};
+use tracing::trace;
+
+use crate::call_context::CallContext;
+use crate::memory::{GuestPtr, GuestSlice};
+use crate::VMError;
+
+type CallArgs = (i32, i32, i32, i32, i32, u64, i32, i32);
+
+pub struct Query;
+
+impl Query {
+    /// Runs a query against another contract, returning the length of the
+    /// return value or a negative [`CallError`] code.
+    pub fn query(context: &mut CallContext, args: CallArgs) -> Result<i32, VMError> {
+        trace!("Executing 'query' host function");
+
+        let config = context.config();
+        context.charge_gas(config.host_costs.query)?;
+
+        call_contract(context, args, false)
+    }
+}
+
+pub struct Transact;
+
+impl Transact {
+    /// Applies a transaction to another contract, returning the length of
+    /// the return value or a negative [`CallError`] code.
+    pub fn transact(context: &mut CallContext, args: CallArgs) -> Result<i32, VMError> {
+        trace!("Executing 'transact' host function");
+
+        let config = context.config();
+        context.charge_gas(config.host_costs.transact)?;
+
+        call_contract(context, args, true)
+    }
+}
+
+fn call_contract(
+    context: &mut CallContext,
+    (target, name, name_len, arg, arg_len, gas_limit, buffer, buffer_len): CallArgs,
+    transact: bool,
+) -> Result<i32, VMError> {
+    let target = ContractId(context.read_pod(GuestPtr::new(target))?);
+    let name = context
+        .read_str(GuestSlice::new(name, name_len)?)?
+        .to_string();
+    let arg = context.read_bytes(GuestSlice::new(arg, arg_len)?)?.to_vec();
+    let buffer = GuestSlice::<u8>::new(buffer, buffer_len)?;
+
+    let result = context.call_contract(target, &name, &arg, gas_limit, transact);
+
+    // the caller may have run out of gas paying for the callee
+    context.charge_gas(0)?;
+
+    let code = match result {
+        Ok(ret) => {
+            let data = ret.data();
+            if data.len() > buffer.len() {
+                CallError::RETURN_TOO_LARGE
+            } else {
+                context.write_memory(data, buffer.offset())?;
+                return Ok(data.len() as i32);
+            }
+        }
+        Err(VMError::ContractAbort { code, .. }) => {
+            if buffer.len() >= 4 {
+                context.write_memory(&code.to_le_bytes(), buffer.offset())?;
+            }
+            CallError::ABORT
+        }
+        Err(VMError::OutOfGas) => CallError::OUT_OF_GAS,
+        Err(VMError::UnknownContract) => CallError::UNKNOWN_CONTRACT,
+        Err(VMError::WasmerExportError(_)) => CallError::UNKNOWN_FUNCTION,
+        Err(_) => CallError::PANIC,
+    };
+
+    trace!("Call to contract failed with code {}", code);
+    Ok(code)
+}
@@ -4,28 +4,36 @@
 //
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
+use primitives::LogLevel;
 use tracing::trace;
 
-use crate::env::Env;
+use crate::call_context::CallContext;
+use crate::memory::GuestSlice;
+use crate::ops::linear_cost;
 use crate::VMError;
 
 pub struct Debug;
 
 impl Debug {
-    pub fn debug(env: &Env, msg_ofs: i32, msg_len: i32) -> Result<(), VMError> {
+    pub fn debug(context: &mut CallContext, (msg_ofs, msg_len): (i32, i32)) -> Result<(), VMError> {
         trace!("Executing 'debug' host function");
 
-        let context = env.get_context();
+        let msg = GuestSlice::new(msg_ofs, msg_len)?;
 
+        // a debug message is a log at the debug level, charged per byte alike
         let config = context.config();
-        context.charge_gas(config.host_costs.debug)?;
-
-        let msg_ofs = msg_ofs as u64;
-        let msg_len = msg_len as usize;
-        let message_memory = context.read_memory(msg_ofs, msg_len)?;
-        let str = std::str::from_utf8(message_memory).map_err(|_| VMError::InvalidUtf8)?;
-
-        println!("{:?}", str);
+        context.charge_gas(linear_cost(
+            config.host_costs.debug,
+            config.host_costs.log_per_byte,
+            msg.len(),
+        ))?;
+
+        if !context.accepts_log(LogLevel::Debug, msg.len()) {
+            return Ok(());
+        }
+        let message = context.read_str(msg)?.to_string();
+
+        context.log(LogLevel::Debug, &message);
 
         Ok(())
     }
@@ -0,0 +1,41 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use tracing::trace;
+
+use crate::call_context::CallContext;
+use crate::memory::GuestSlice;
+use crate::ops::linear_cost;
+use crate::VMError;
+
+pub struct Emit;
+
+impl Emit {
+    pub fn emit(
+        context: &mut CallContext,
+        (topic_ofs, topic_len, data_ofs, data_len): (i32, i32, i32, i32),
+    ) -> Result<(), VMError> {
+        trace!("Executing 'emit' host function");
+
+        let topic = GuestSlice::new(topic_ofs, topic_len)?;
+        let data = GuestSlice::new(data_ofs, data_len)?;
+
+        let config = context.config();
+        let bytes = topic.len() + data.len();
+        context.charge_gas(linear_cost(
+            config.host_costs.emit,
+            config.host_costs.emit_per_byte,
+            bytes,
+        ))?;
+
+        let topic = context.read_str(topic)?.to_string();
+        let data = context.read_bytes(data)?.to_vec();
+
+        context.emit(topic, data);
+
+        Ok(())
+    }
+}
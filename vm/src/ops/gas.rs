@@ -6,17 +6,15 @@
 
 use tracing::trace;
 
-use crate::env::Env;
+use crate::call_context::CallContext;
 use crate::VMError;
 
 pub struct GasConsumed;
 
 impl GasConsumed {
-    pub fn gas_consumed(env: &Env) -> Result<u64, VMError> {
+    pub fn gas_consumed(context: &mut CallContext, _: ()) -> Result<u64, VMError> {
         trace!("Executing 'gas_consumed' host function");
 
-        let context = env.get_context();
-
         let config = context.config();
         context.charge_gas(config.host_costs.gas_consumed)?;
 
@@ -27,11 +25,9 @@ impl GasConsumed {
 pub struct GasLeft;
 
 impl GasLeft {
-    pub fn gas_left(env: &Env) -> Result<u64, VMError> {
+    pub fn gas_left(context: &mut CallContext, _: ()) -> Result<u64, VMError> {
         trace!("Executing 'gas_left' host function");
 
-        let context = env.get_context();
-
         let config = context.config();
         context.charge_gas(config.host_costs.gas_left)?;
 
@@ -0,0 +1,172 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use primitives::native::{blake2b, keccak256, sha512, Hasher};
+use primitives::HashAlgorithm;
+use tracing::trace;
+
+use crate::call_context::CallContext;
+use crate::memory::{GuestPtr, GuestSlice};
+use crate::ops::linear_cost;
+use crate::VMError;
+
+/// Maximum length of a blake2b digest
+const BLAKE2B_MAX_LEN: usize = 64;
+/// Maximum length of a blake2b personalization
+const BLAKE2B_MAX_PERSONAL_LEN: usize = 16;
+
+pub struct Blake2b;
+
+impl Blake2b {
+    pub fn blake2b(
+        context: &mut CallContext,
+        (input, input_len, personal, personal_len, output, output_len): (
+            i32,
+            i32,
+            i32,
+            i32,
+            i32,
+            i32,
+        ),
+    ) -> Result<(), VMError> {
+        trace!("Executing 'blake2b' host function");
+
+        let input = GuestSlice::new(input, input_len)?;
+        let personal = GuestSlice::new(personal, personal_len)?;
+        let output = GuestSlice::new(output, output_len)?;
+
+        if output.is_empty()
+            || output.len() > BLAKE2B_MAX_LEN
+            || personal.len() > BLAKE2B_MAX_PERSONAL_LEN
+        {
+            return Err(VMError::InvalidData);
+        }
+
+        let config = context.config();
+        context.charge_gas(linear_cost(
+            config.host_costs.blake2b,
+            config.host_costs.blake2b_per_byte,
+            input.len(),
+        ))?;
+
+        let hash = blake2b(
+            context.read_bytes(input)?,
+            context.read_bytes(personal)?,
+            output.len(),
+        );
+
+        context.write_bytes(output, hash.as_bytes())
+    }
+}
+
+pub struct Keccak256;
+
+impl Keccak256 {
+    pub fn keccak256(
+        context: &mut CallContext,
+        (input, input_len, output): (i32, i32, i32),
+    ) -> Result<(), VMError> {
+        trace!("Executing 'keccak256' host function");
+
+        let input = GuestSlice::new(input, input_len)?;
+
+        let config = context.config();
+        context.charge_gas(linear_cost(
+            config.host_costs.keccak256,
+            config.host_costs.keccak256_per_byte,
+            input.len(),
+        ))?;
+
+        let out = keccak256(context.read_bytes(input)?);
+
+        context.write_pod(GuestPtr::new(output), &out)
+    }
+}
+
+pub struct Sha512;
+
+impl Sha512 {
+    pub fn sha512(
+        context: &mut CallContext,
+        (input, input_len, output): (i32, i32, i32),
+    ) -> Result<(), VMError> {
+        trace!("Executing 'sha512' host function");
+
+        let input = GuestSlice::new(input, input_len)?;
+
+        let config = context.config();
+        context.charge_gas(linear_cost(
+            config.host_costs.sha512,
+            config.host_costs.sha512_per_byte,
+            input.len(),
+        ))?;
+
+        let out = sha512(context.read_bytes(input)?);
+
+        context.write_pod(GuestPtr::new(output), &out)
+    }
+}
+
+pub struct HashInit;
+
+impl HashInit {
+    pub fn hash_init(context: &mut CallContext, (algorithm,): (i32,)) -> Result<i32, VMError> {
+        trace!("Executing 'hash_init' host function");
+
+        let config = context.config();
+        context.charge_gas(config.host_costs.hash_init)?;
+
+        let algorithm = HashAlgorithm::from_i32(algorithm).ok_or(VMError::InvalidData)?;
+
+        context.insert_hasher(Hasher::new(algorithm))
+    }
+}
+
+pub struct HashUpdate;
+
+impl HashUpdate {
+    pub fn hash_update(
+        context: &mut CallContext,
+        (handle, input, input_len): (i32, i32, i32),
+    ) -> Result<(), VMError> {
+        trace!("Executing 'hash_update' host function");
+
+        let input = GuestSlice::new(input, input_len)?;
+
+        let config = context.config();
+        context.charge_gas(linear_cost(
+            config.host_costs.hash_update,
+            config.host_costs.hash_update_per_byte,
+            input.len(),
+        ))?;
+
+        context.update_hasher(handle, input)
+    }
+}
+
+pub struct HashFinalize;
+
+impl HashFinalize {
+    pub fn hash_finalize(
+        context: &mut CallContext,
+        (handle, output, output_len): (i32, i32, i32),
+    ) -> Result<i32, VMError> {
+        trace!("Executing 'hash_finalize' host function");
+
+        let config = context.config();
+        context.charge_gas(config.host_costs.hash_finalize)?;
+
+        let digest = context.take_hasher(handle)?.finalize();
+
+        let output = GuestSlice::<u8>::new(output, output_len)?;
+        if output.len() < digest.len() {
+            return Err(VMError::InvalidData);
+        }
+        context.write_memory(&digest, output.offset())?;
+
+        Ok(digest.len() as i32)
+    }
+}
@@ -0,0 +1,43 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use primitives::LogLevel;
+use tracing::trace;
+
+use crate::call_context::CallContext;
+use crate::memory::GuestSlice;
+use crate::ops::linear_cost;
+use crate::VMError;
+
+pub struct Log;
+
+impl Log {
+    pub fn log(
+        context: &mut CallContext,
+        (level, msg_ofs, msg_len): (i32, i32, i32),
+    ) -> Result<(), VMError> {
+        trace!("Executing 'log' host function");
+
+        let msg = GuestSlice::new(msg_ofs, msg_len)?;
+
+        let config = context.config();
+        context.charge_gas(linear_cost(
+            config.host_costs.log,
+            config.host_costs.log_per_byte,
+            msg.len(),
+        ))?;
+
+        let level = LogLevel::from_i32(level).ok_or(VMError::InvalidData)?;
+        if !context.accepts_log(level, msg.len()) {
+            return Ok(());
+        }
+        let message = context.read_str(msg)?.to_string();
+
+        context.log(level, &message);
+
+        Ok(())
+    }
+}
@@ -4,6 +4,22 @@
 //
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
+pub mod abort;
+pub mod call;
 pub mod debug;
+pub mod emit;
 pub mod gas;
+pub mod hash;
+pub mod log;
 pub mod sha256;
+pub mod signature;
+pub mod store;
+pub mod zk;
+
+use crate::Gas;
+
+/// Cost of a host function charging `base` plus `per_unit` for each of
+/// `units`, saturating instead of overflowing.
+pub(crate) fn linear_cost(base: Gas, per_unit: Gas, units: usize) -> Gas {
+    base.saturating_add(per_unit.saturating_mul(units as u64))
+}
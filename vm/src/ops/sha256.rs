@@ -4,30 +4,47 @@
 //
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
+use primitives::native;
 use tracing::trace;
 
-use crate::env::Env;
+use crate::call_context::CallContext;
+use crate::memory::{GuestPtr, GuestSlice};
 use crate::VMError;
 
 pub struct Sha256;
 
 impl Sha256 {
-    pub fn sha256(env: &Env, input: i32, input_len: i32, output: i32) -> Result<(), VMError> {
+    /// Writes the hex encoded digest, 64 bytes, to `output`.
+    pub fn sha256(
+        context: &mut CallContext,
+        (input, input_len, output): (i32, i32, i32),
+    ) -> Result<(), VMError> {
         trace!("Executing 'sha256' host function");
 
-        let context = env.get_context();
-
         let config = context.config();
         context.charge_gas(config.host_costs.sha256)?;
 
-        let input_ofs = input as u64;
-        let msg_input_len = input_len as usize;
-        let input_memory = context.read_memory(input_ofs, msg_input_len)?;
+        let input = context.read_bytes(GuestSlice::new(input, input_len)?)?;
+
+        let out = sha256::digest(input);
+
+        context.write_bytes(GuestSlice::new(output, out.len() as i32)?, out.as_bytes())
+    }
+
+    /// Writes the raw digest, 32 bytes, to `output`.
+    pub fn sha256_v2(
+        context: &mut CallContext,
+        (input, input_len, output): (i32, i32, i32),
+    ) -> Result<(), VMError> {
+        trace!("Executing 'sha256' host function");
+
+        let config = context.config();
+        context.charge_gas(config.host_costs.sha256)?;
 
-        let out = sha256::digest(input_memory);
+        let input = context.read_bytes(GuestSlice::new(input, input_len)?)?;
 
-        let _result_ofs = output;
+        let out = native::sha256(input);
 
-        context.write_memory(out.as_bytes(), _result_ofs as u64)
+        context.write_pod(GuestPtr::new(output), &out)
     }
 }
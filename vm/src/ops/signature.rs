@@ -0,0 +1,137 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use primitives::native::{
+    recover_secp256k1, verify_ed25519, verify_ed25519_batch, verify_secp256k1,
+};
+use primitives::Ed25519BatchItem;
+use tracing::trace;
+
+use crate::call_context::CallContext;
+use crate::memory::{GuestPtr, GuestSlice};
+use crate::ops::linear_cost;
+use crate::VMError;
+
+pub struct VerifyEd25519;
+
+impl VerifyEd25519 {
+    pub fn verify_ed25519(
+        context: &mut CallContext,
+        (pk, msg, msg_len, sig): (i32, i32, i32, i32),
+    ) -> Result<i32, VMError> {
+        trace!("Executing 'verify_ed25519' host function");
+
+        let msg = GuestSlice::new(msg, msg_len)?;
+
+        let config = context.config();
+        context.charge_gas(linear_cost(
+            config.host_costs.verify_ed25519,
+            config.host_costs.verify_ed25519_per_byte,
+            msg.len(),
+        ))?;
+
+        let pk: [u8; 32] = context.read_pod(GuestPtr::new(pk))?;
+        let sig: [u8; 64] = context.read_pod(GuestPtr::new(sig))?;
+        let msg = context.read_bytes(msg)?;
+
+        Ok(verify_ed25519(&pk, msg, &sig) as i32)
+    }
+}
+
+pub struct VerifyEd25519Batch;
+
+impl VerifyEd25519Batch {
+    /// Verifies an archived `Vec<Ed25519BatchItem>`, returning `-1` if all
+    /// signatures are valid or the index of the first invalid one otherwise.
+    ///
+    /// Items are cheaper than single verifications while the batch holds.
+    /// Finding the invalid item of a failed batch costs what verifying each
+    /// item alone does.
+    pub fn verify_ed25519_batch(
+        context: &mut CallContext,
+        (items, items_len): (i32, i32),
+    ) -> Result<i32, VMError> {
+        trace!("Executing 'verify_ed25519_batch' host function");
+
+        let items = GuestSlice::new(items, items_len)?;
+
+        let config = context.config();
+        context.charge_gas(linear_cost(
+            config.host_costs.verify_ed25519_batch,
+            config.host_costs.verify_ed25519_per_byte,
+            items.len(),
+        ))?;
+
+        let items: Vec<Ed25519BatchItem> = context.read_archived(items)?;
+        context.charge_gas(linear_cost(
+            0,
+            config.host_costs.verify_ed25519_batch_per_item,
+            items.len(),
+        ))?;
+
+        if verify_ed25519_batch(&items) {
+            return Ok(-1);
+        }
+
+        context.charge_gas(linear_cost(
+            0,
+            config.host_costs.verify_ed25519,
+            items.len(),
+        ))?;
+        let invalid = items
+            .iter()
+            .position(|item| !verify_ed25519(&item.pk, &item.msg, &item.sig));
+
+        Ok(invalid.map_or(-1, |i| i as i32))
+    }
+}
+
+pub struct VerifySecp256k1;
+
+impl VerifySecp256k1 {
+    pub fn verify_secp256k1(
+        context: &mut CallContext,
+        (pk, pk_len, msg, sig): (i32, i32, i32, i32),
+    ) -> Result<i32, VMError> {
+        trace!("Executing 'verify_secp256k1' host function");
+
+        let config = context.config();
+        context.charge_gas(config.host_costs.verify_secp256k1)?;
+
+        let pk = context.read_bytes(GuestSlice::new(pk, pk_len)?)?;
+        let msg: [u8; 32] = context.read_pod(GuestPtr::new(msg))?;
+        let sig: [u8; 64] = context.read_pod(GuestPtr::new(sig))?;
+
+        Ok(verify_secp256k1(pk, &msg, &sig) as i32)
+    }
+}
+
+pub struct RecoverSecp256k1;
+
+impl RecoverSecp256k1 {
+    /// Recovers the uncompressed public key from a 65 byte signature, whose
+    /// last byte is the recovery id - either `0`/`1` or Ethereum's `27`/`28`.
+    pub fn recover_secp256k1(
+        context: &mut CallContext,
+        (msg, sig, output): (i32, i32, i32),
+    ) -> Result<i32, VMError> {
+        trace!("Executing 'recover_secp256k1' host function");
+
+        let config = context.config();
+        context.charge_gas(config.host_costs.recover_secp256k1)?;
+
+        let msg: [u8; 32] = context.read_pod(GuestPtr::new(msg))?;
+        let sig: [u8; 65] = context.read_pod(GuestPtr::new(sig))?;
+
+        match recover_secp256k1(&msg, &sig) {
+            Some(pk) => {
+                context.write_pod(GuestPtr::new(output), &pk)?;
+                Ok(1)
+            }
+            None => Ok(0),
+        }
+    }
+}
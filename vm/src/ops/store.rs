@@ -0,0 +1,55 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use tracing::trace;
+
+use crate::call_context::CallContext;
+use crate::memory::GuestSlice;
+use crate::ops::linear_cost;
+use crate::VMError;
+
+pub struct Put;
+
+impl Put {
+    /// Stores the bytes of a slice, returning the offset to get them back by.
+    pub fn put(context: &mut CallContext, (slice, len): (i32, i32)) -> Result<u64, VMError> {
+        trace!("Executing '_put' host function");
+
+        let slice = GuestSlice::new(slice, len)?;
+
+        let config = context.config();
+        context.charge_gas(linear_cost(
+            config.host_costs.store_put,
+            config.host_costs.store_put_per_byte,
+            slice.len(),
+        ))?;
+
+        context.store_put(slice)
+    }
+}
+
+pub struct Get;
+
+impl Get {
+    /// Copies `len` stored bytes at `offset` into the buffer.
+    pub fn get(
+        context: &mut CallContext,
+        (offset, len, buffer): (u64, i32, i32),
+    ) -> Result<(), VMError> {
+        trace!("Executing '_get' host function");
+
+        let buffer = GuestSlice::new(buffer, len)?;
+
+        let config = context.config();
+        context.charge_gas(linear_cost(
+            config.host_costs.store_get,
+            config.host_costs.store_get_per_byte,
+            buffer.len(),
+        ))?;
+
+        context.store_get(offset, buffer)
+    }
+}
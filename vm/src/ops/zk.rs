@@ -0,0 +1,119 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use primitives::native::{self, PAIR_SIZE};
+use tracing::trace;
+
+use crate::call_context::CallContext;
+use crate::memory::{GuestPtr, GuestSlice};
+use crate::ops::linear_cost;
+use crate::VMError;
+
+pub struct PoseidonHash;
+
+impl PoseidonHash {
+    /// Hashes `inputs_len` scalars, returning `0` if any of them is not
+    /// canonical.
+    pub fn poseidon_hash(
+        context: &mut CallContext,
+        (inputs, inputs_len, output): (i32, i32, i32),
+    ) -> Result<i32, VMError> {
+        trace!("Executing 'poseidon_hash' host function");
+
+        let inputs = GuestSlice::<[u8; 32]>::new(inputs, inputs_len)?;
+
+        let config = context.config();
+        context.charge_gas(linear_cost(
+            config.host_costs.poseidon_hash,
+            config.host_costs.poseidon_hash_per_scalar,
+            inputs.len(),
+        ))?;
+
+        match native::poseidon_hash(&context.read_slice(inputs)?) {
+            Some(hash) => {
+                context.write_pod(GuestPtr::new(output), &hash)?;
+                Ok(1)
+            }
+            None => Ok(0),
+        }
+    }
+}
+
+pub struct JubJubAdd;
+
+impl JubJubAdd {
+    /// Adds two compressed points, returning `0` if either is not valid.
+    pub fn jubjub_add(
+        context: &mut CallContext,
+        (a, b, output): (i32, i32, i32),
+    ) -> Result<i32, VMError> {
+        trace!("Executing 'jubjub_add' host function");
+
+        context.charge_gas(context.config().host_costs.jubjub_add)?;
+
+        let a: [u8; 32] = context.read_pod(GuestPtr::new(a))?;
+        let b: [u8; 32] = context.read_pod(GuestPtr::new(b))?;
+
+        match native::jubjub_add(&a, &b) {
+            Some(sum) => {
+                context.write_pod(GuestPtr::new(output), &sum)?;
+                Ok(1)
+            }
+            _ => Ok(0),
+        }
+    }
+}
+
+pub struct JubJubMul;
+
+impl JubJubMul {
+    /// Multiplies a compressed point by a scalar, returning `0` if either is
+    /// not valid.
+    pub fn jubjub_mul(
+        context: &mut CallContext,
+        (point, scalar, output): (i32, i32, i32),
+    ) -> Result<i32, VMError> {
+        trace!("Executing 'jubjub_mul' host function");
+
+        context.charge_gas(context.config().host_costs.jubjub_mul)?;
+
+        let point: [u8; 32] = context.read_pod(GuestPtr::new(point))?;
+        let scalar: [u8; 32] = context.read_pod(GuestPtr::new(scalar))?;
+
+        match native::jubjub_mul(&point, &scalar) {
+            Some(product) => {
+                context.write_pod(GuestPtr::new(output), &product)?;
+                Ok(1)
+            }
+            _ => Ok(0),
+        }
+    }
+}
+
+pub struct BlsPairingCheck;
+
+impl BlsPairingCheck {
+    /// Checks that the product of the pairings of `pairs_len` G1/G2 pairs is
+    /// the identity, returning `1` if it is and `0` if it isn't or any of
+    /// the points is not valid.
+    pub fn bls_pairing_check(
+        context: &mut CallContext,
+        (pairs, pairs_len): (i32, i32),
+    ) -> Result<i32, VMError> {
+        trace!("Executing 'bls_pairing_check' host function");
+
+        let pairs = GuestSlice::<[u8; PAIR_SIZE]>::new(pairs, pairs_len)?;
+
+        let config = context.config();
+        context.charge_gas(linear_cost(
+            config.host_costs.bls_pairing_check,
+            config.host_costs.bls_pairing_check_per_pair,
+            pairs.len(),
+        ))?;
+
+        Ok(native::bls_pairing_check(&context.read_slice(pairs)?) as i32)
+    }
+}
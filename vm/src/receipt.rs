@@ -0,0 +1,256 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Outcome of the execution of a contract.
+
+use bytecheck::CheckBytes;
+use primitives::{LogLevel, ReturnValue};
+use rkyv::Archive;
+use serde::{Deserialize, Serialize};
+
+use crate::backtrace::Backtrace;
+use crate::VMError;
+
+/// An event emitted by a contract.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    call: usize,
+    topic: String,
+    data: Vec<u8>,
+}
+
+impl Event {
+    pub(crate) fn new(call: usize, topic: String, data: Vec<u8>) -> Self {
+        Event { call, topic, data }
+    }
+
+    /// Returns the index of the call that emitted the event, counting the
+    /// calls of the execution in the order they were made, from `0` for the
+    /// outermost one. Events of the same call share it.
+    pub fn call(&self) -> usize {
+        self.call
+    }
+
+    /// Returns the topic of the event.
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    /// Returns the archived data of the event.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+/// A message logged by a contract.
+#[derive(
+    Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Archive, rkyv::Serialize, rkyv::Deserialize,
+)]
+#[archive_attr(derive(CheckBytes))]
+pub struct LogRecord {
+    #[serde(with = "log_level")]
+    level: LogLevel,
+    message: String,
+}
+
+/// Serializes a [`LogLevel`] as the number contracts pass it to the host as.
+mod log_level {
+    use primitives::LogLevel;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(level: &LogLevel, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i32(*level as i32)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<LogLevel, D::Error> {
+        let level = i32::deserialize(deserializer)?;
+        LogLevel::from_i32(level)
+            .ok_or_else(|| D::Error::custom(format!("invalid log level {}", level)))
+    }
+}
+
+impl LogRecord {
+    pub(crate) fn new(level: LogLevel, message: String) -> Self {
+        LogRecord { level, message }
+    }
+
+    /// Returns the level of the message.
+    pub fn level(&self) -> LogLevel {
+        self.level
+    }
+
+    /// Returns the message.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+/// The receipt of a successful execution.
+#[derive(Debug, Default)]
+pub struct Receipt {
+    events: Vec<Event>,
+    logs: Vec<LogRecord>,
+    ret: ReturnValue,
+}
+
+impl Receipt {
+    pub(crate) fn new(events: Vec<Event>, logs: Vec<LogRecord>) -> Self {
+        Receipt {
+            events,
+            logs,
+            ret: ReturnValue::default(),
+        }
+    }
+
+    pub(crate) fn with_return(mut self, ret: ReturnValue) -> Self {
+        self.ret = ret;
+        self
+    }
+
+    /// Returns the value returned by the called function, empty for
+    /// functions without arguments or outputs.
+    pub fn return_value(&self) -> &ReturnValue {
+        &self.ret
+    }
+
+    /// Returns the messages logged during the execution, in order, from
+    /// [`Config::min_log_level`](crate::Config::min_log_level) up and up to
+    /// [`Config::max_debug_bytes`](crate::Config::max_debug_bytes).
+    pub fn logs(&self) -> &[LogRecord] {
+        &self.logs
+    }
+
+    /// Returns the events emitted during the execution, in order. Events
+    /// emitted by frames that failed are not included.
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+}
+
+/// The receipt of a failed execution, which unlike [`VMError`] can be
+/// serialized with serde or rkyv to be stored or sent.
+#[derive(
+    Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Archive, rkyv::Serialize, rkyv::Deserialize,
+)]
+#[archive_attr(derive(CheckBytes))]
+pub struct ErrorReceipt {
+    code: u32,
+    message: String,
+    backtrace: Option<Backtrace>,
+    logs: Vec<LogRecord>,
+}
+
+impl ErrorReceipt {
+    /// Returns the [`code`](VMError::code) of the error.
+    pub fn code(&self) -> u32 {
+        self.code
+    }
+
+    /// Returns the message of the error.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns the backtrace of the guest, if it trapped or panicked.
+    pub fn backtrace(&self) -> Option<&Backtrace> {
+        self.backtrace.as_ref()
+    }
+
+    /// Returns the messages logged before the execution failed, as
+    /// [`Receipt::logs`] does for a successful one.
+    pub fn logs(&self) -> &[LogRecord] {
+        &self.logs
+    }
+
+    pub(crate) fn with_logs(mut self, logs: Vec<LogRecord>) -> Self {
+        self.logs = logs;
+        self
+    }
+}
+
+impl From<&VMError> for ErrorReceipt {
+    fn from(error: &VMError) -> Self {
+        let message = match error {
+            // the backtrace is kept apart
+            VMError::ExecutionPanic { message, .. } => {
+                format!("Panicked with message: {}", message)
+            }
+            VMError::WasmerTrap { code, .. } => format!("WASMER trap: {}", code),
+            error => error.to_string(),
+        };
+
+        ErrorReceipt {
+            code: error.code(),
+            message,
+            backtrace: error.backtrace().cloned(),
+            logs: vec![],
+        }
+    }
+}
+
+impl From<VMError> for ErrorReceipt {
+    fn from(error: VMError) -> Self {
+        Self::from(&error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TrapCode;
+
+    fn receipts() -> Vec<ErrorReceipt> {
+        vec![
+            VMError::WasmerTrap {
+                code: TrapCode::UnreachableCodeReached,
+                backtrace: Backtrace::example(),
+            }
+            .into(),
+            VMError::ExecutionPanic {
+                message: "index out of bounds".to_string(),
+                backtrace: Backtrace::example(),
+            }
+            .into(),
+            ErrorReceipt::from(VMError::OutOfGas).with_logs(vec![
+                LogRecord::new(LogLevel::Info, "spinning".to_string()),
+                LogRecord::new(LogLevel::Error, "still spinning".to_string()),
+            ]),
+        ]
+    }
+
+    #[test]
+    fn trap_receipt() {
+        let receipt = ErrorReceipt::from(VMError::WasmerTrap {
+            code: TrapCode::UnreachableCodeReached,
+            backtrace: Backtrace::example(),
+        });
+
+        assert_eq!(receipt.code(), 15);
+        assert_eq!(receipt.message(), "WASMER trap: unreachable");
+        assert_eq!(receipt.backtrace(), Some(&Backtrace::example()));
+    }
+
+    #[test]
+    fn error_receipt_serde_roundtrip() {
+        for receipt in receipts() {
+            let json = serde_json::to_string(&receipt).unwrap();
+            let decoded: ErrorReceipt = serde_json::from_str(&json).unwrap();
+            assert_eq!(decoded, receipt);
+        }
+    }
+
+    #[test]
+    fn error_receipt_rkyv_roundtrip() {
+        for receipt in receipts() {
+            let bytes = rkyv::to_bytes::<_, 256>(&receipt).unwrap();
+            let archived = rkyv::check_archived_root::<ErrorReceipt>(&bytes).unwrap();
+            let decoded: ErrorReceipt =
+                rkyv::Deserialize::deserialize(archived, &mut rkyv::Infallible).unwrap();
+            assert_eq!(decoded, receipt);
+        }
+    }
+}
@@ -4,46 +4,140 @@
 //
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
+use std::collections::BTreeMap;
+
 use crate::env::Env;
+use crate::host_function::{HostFunction, CRYPTO, DEFAULT_NAMESPACE, VM_V1, VM_V2};
 use crate::ops::*;
+use crate::VMError;
 
-use wasmer::{Exports, Function, Store};
+use wasmer::{Exports, Store};
 
-pub struct HostImportsResolver;
+/// Registry of the host functions available to guests, indexed by namespace
+/// and name.
+#[derive(Clone, Default)]
+pub struct HostImportsResolver {
+    functions: BTreeMap<(String, String), HostFunction>,
+}
 
 impl HostImportsResolver {
+    /// Returns a resolver with the built-in host functions registered.
+    pub fn new() -> Self {
+        const ALL: &[&str] = &[DEFAULT_NAMESPACE, VM_V1, VM_V2];
+        const V1: &[&str] = &[DEFAULT_NAMESPACE, VM_V1];
+
+        let mut resolver = Self::default();
+
+        resolver.insert_all(ALL, HostFunction::new("debug", debug::Debug::debug));
+        resolver.insert_all(
+            ALL,
+            HostFunction::new("gas_consumed", gas::GasConsumed::gas_consumed),
+        );
+        resolver.insert_all(ALL, HostFunction::new("gas_left", gas::GasLeft::gas_left));
+
+        resolver.insert_all(V1, HostFunction::new("sha256", sha256::Sha256::sha256));
+        resolver.insert(HostFunction::new("sha256", sha256::Sha256::sha256_v2).namespace(VM_V2));
+
+        resolver.insert(HostFunction::new("_put", store::Put::put));
+        resolver.insert(HostFunction::new("_get", store::Get::get));
+
+        resolver.insert(HostFunction::new("log", log::Log::log).namespace(VM_V2));
+        resolver.insert(HostFunction::new("abort", abort::Abort::abort).namespace(VM_V2));
+        resolver.insert(HostFunction::new("emit", emit::Emit::emit).namespace(VM_V2));
+        resolver.insert(HostFunction::new("query", call::Query::query).namespace(VM_V2));
+        resolver.insert(HostFunction::new("transact", call::Transact::transact).namespace(VM_V2));
+
+        resolver.insert(HostFunction::new("blake2b", hash::Blake2b::blake2b).namespace(CRYPTO));
+        resolver
+            .insert(HostFunction::new("keccak256", hash::Keccak256::keccak256).namespace(CRYPTO));
+        resolver.insert(HostFunction::new("sha512", hash::Sha512::sha512).namespace(CRYPTO));
+        resolver
+            .insert(HostFunction::new("hash_init", hash::HashInit::hash_init).namespace(CRYPTO));
+        resolver.insert(
+            HostFunction::new("hash_update", hash::HashUpdate::hash_update).namespace(CRYPTO),
+        );
+        resolver.insert(
+            HostFunction::new("hash_finalize", hash::HashFinalize::hash_finalize).namespace(CRYPTO),
+        );
+
+        resolver.insert(
+            HostFunction::new("verify_ed25519", signature::VerifyEd25519::verify_ed25519)
+                .namespace(CRYPTO),
+        );
+        resolver.insert(
+            HostFunction::new(
+                "verify_ed25519_batch",
+                signature::VerifyEd25519Batch::verify_ed25519_batch,
+            )
+            .namespace(CRYPTO),
+        );
+        resolver.insert(
+            HostFunction::new(
+                "verify_secp256k1",
+                signature::VerifySecp256k1::verify_secp256k1,
+            )
+            .namespace(CRYPTO),
+        );
+        resolver.insert(
+            HostFunction::new(
+                "recover_secp256k1",
+                signature::RecoverSecp256k1::recover_secp256k1,
+            )
+            .namespace(CRYPTO),
+        );
+
+        resolver.insert(
+            HostFunction::new("poseidon_hash", zk::PoseidonHash::poseidon_hash).namespace(CRYPTO),
+        );
+        resolver
+            .insert(HostFunction::new("jubjub_add", zk::JubJubAdd::jubjub_add).namespace(CRYPTO));
+        resolver
+            .insert(HostFunction::new("jubjub_mul", zk::JubJubMul::jubjub_mul).namespace(CRYPTO));
+        resolver.insert(
+            HostFunction::new("bls_pairing_check", zk::BlsPairingCheck::bls_pairing_check)
+                .namespace(CRYPTO),
+        );
+
+        resolver
+    }
+
+    /// Registers a host function under each of the given namespaces.
+    fn insert_all(&mut self, namespaces: &[&str], function: HostFunction) {
+        for namespace in namespaces {
+            self.insert(function.clone().namespace(*namespace));
+        }
+    }
+
+    /// Registers a host function, replacing any function previously
+    /// registered under the same namespace and name.
+    pub fn insert(&mut self, function: HostFunction) {
+        let key = (
+            function.namespace_name().to_string(),
+            function.name().to_string(),
+        );
+        self.functions.insert(key, function);
+    }
+
+    /// Binds the host functions a module imports from a namespace to `env`,
+    /// failing if any of them is not registered.
     pub fn insert_into_namespace(
+        &self,
         namespace: &mut Exports,
+        namespace_name: &str,
         store: &Store,
         env: Env,
         names: &[String],
-    ) {
+    ) -> Result<(), VMError> {
         for name in names {
-            match name.as_str() {
-                "debug" => namespace.insert(
-                    "debug",
-                    Function::new_native_with_env(store, env.clone(), debug::Debug::debug),
-                ),
-                "gas_consumed" => namespace.insert(
-                    "gas_consumed",
-                    Function::new_native_with_env(
-                        store,
-                        env.clone(),
-                        gas::GasConsumed::gas_consumed,
-                    ),
-                ),
-                "gas_left" => namespace.insert(
-                    "gas_left",
-                    Function::new_native_with_env(store, env.clone(), gas::GasLeft::gas_left),
-                ),
-                "sha256" => namespace.insert(
-                    "sha256",
-                    Function::new_native_with_env(store, env.clone(), sha256::Sha256::sha256),
-                ),
-                _ => {
-                    debug_assert!(false, "unknown wasm module import {}", name)
-                }
-            }
+            let key = (namespace_name.to_string(), name.clone());
+            let function = self.functions.get(&key).ok_or_else(|| {
+                VMError::WasmerInstantiationError(format!(
+                    "unknown import {}::{}",
+                    namespace_name, name
+                ))
+            })?;
+            namespace.insert(name.as_str(), function.to_function(store, env.clone()));
         }
+        Ok(())
     }
 }
@@ -0,0 +1,105 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Destinations for the debug output of contracts.
+
+use std::sync::{Arc, Mutex};
+
+use primitives::LogLevel;
+
+use crate::receipt::LogRecord;
+
+/// Receives the messages contracts log, through the `log` host function or
+/// the `debug` one at [`LogLevel::Debug`].
+pub trait DebugSink: Send + Sync {
+    /// Handle a log message.
+    fn debug(&self, level: LogLevel, message: &str);
+}
+
+/// Prints log messages to stdout.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StdoutSink;
+
+impl DebugSink for StdoutSink {
+    fn debug(&self, level: LogLevel, message: &str) {
+        println!("{} {:?}", level, message);
+    }
+}
+
+/// Emits log messages as `tracing` events of the matching level.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TracingSink;
+
+impl DebugSink for TracingSink {
+    fn debug(&self, level: LogLevel, message: &str) {
+        match level {
+            LogLevel::Trace => tracing::trace!(target: "contract", "{}", message),
+            LogLevel::Debug => tracing::debug!(target: "contract", "{}", message),
+            LogLevel::Info => tracing::info!(target: "contract", "{}", message),
+            LogLevel::Warn => tracing::warn!(target: "contract", "{}", message),
+            LogLevel::Error => tracing::error!(target: "contract", "{}", message),
+        }
+    }
+}
+
+/// Collects log messages in memory. Clones share the same buffer.
+#[derive(Debug, Default, Clone)]
+pub struct BufferSink {
+    records: Arc<Mutex<Vec<LogRecord>>>,
+}
+
+impl BufferSink {
+    /// Returns a new empty [`BufferSink`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the messages collected so far.
+    pub fn messages(&self) -> Vec<String> {
+        self.records
+            .lock()
+            .expect("poisoned buffer")
+            .iter()
+            .map(|record| record.message().to_string())
+            .collect()
+    }
+
+    /// Returns the messages collected so far, with their levels.
+    pub fn records(&self) -> Vec<LogRecord> {
+        self.records.lock().expect("poisoned buffer").clone()
+    }
+
+    /// Returns the messages collected so far, clearing the buffer.
+    pub fn take(&self) -> Vec<String> {
+        self.take_records()
+            .into_iter()
+            .map(|record| record.message().to_string())
+            .collect()
+    }
+
+    /// Returns the messages collected so far with their levels, clearing
+    /// the buffer.
+    pub fn take_records(&self) -> Vec<LogRecord> {
+        std::mem::take(&mut *self.records.lock().expect("poisoned buffer"))
+    }
+}
+
+impl DebugSink for BufferSink {
+    fn debug(&self, level: LogLevel, message: &str) {
+        self.records
+            .lock()
+            .expect("poisoned buffer")
+            .push(LogRecord::new(level, message.to_string()));
+    }
+}
+
+/// Discards log messages.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoopSink;
+
+impl DebugSink for NoopSink {
+    fn debug(&self, _: LogLevel, _: &str) {}
+}
@@ -4,30 +4,58 @@
 //
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
+use bytecheck::CheckBytes;
+use primitives::{ContractId, Query, Transaction};
+use rkyv::ser::serializers::AllocSerializer;
+use rkyv::validation::validators::DefaultValidator;
+use rkyv::{Archive, Deserialize, Infallible, Serialize};
 use tracing::{trace, trace_span};
 
 use crate::call_context::CallContext;
 use crate::config::{Config, DEFAULT_CONFIG};
 use crate::error::VMError;
 use crate::gas::GasMeter;
+use crate::host_function::HostFunction;
+use crate::memory::deserialize_archived;
+use crate::receipt::{ErrorReceipt, LogRecord, Receipt};
+use crate::resolver::HostImportsResolver;
+use crate::sink::{DebugSink, StdoutSink};
+
+/// Size of the scratch space used when archiving states and arguments
+const ARCHIVE_SCRATCH_SIZE: usize = 1024;
+
+/// A deployed contract.
+struct Contract {
+    code: Arc<[u8]>,
+    state: Vec<u8>,
+}
 
 /// WASM stack based virtual machine.
 #[derive(Clone)]
 pub struct Vm {
     config: &'static Config,
+    host_functions: HostImportsResolver,
+    debug_sink: Arc<dyn DebugSink>,
+    contracts: Arc<RwLock<BTreeMap<ContractId, Contract>>>,
 }
 
 impl Vm {
     /// Returns a new empty [`Vm`] with the default configuration.
     pub fn new() -> Self {
-        Vm {
-            config: &DEFAULT_CONFIG,
-        }
+        Self::with_config(&DEFAULT_CONFIG)
     }
 
     /// Returns a new empty [`Vm`] with the given configuration.
     pub fn with_config(config: &'static Config) -> Self {
-        Vm { config }
+        Vm {
+            config,
+            host_functions: HostImportsResolver::new(),
+            debug_sink: Arc::new(StdoutSink),
+            contracts: Arc::new(RwLock::new(BTreeMap::new())),
+        }
     }
 
     /// Returns the configuration of this instance.
@@ -35,13 +63,126 @@ impl Vm {
         self.config
     }
 
+    /// Registers a host function guests can import, replacing any function
+    /// previously registered under the same namespace and name - including
+    /// the built-in ones.
+    pub fn register_host_function(&mut self, function: HostFunction) {
+        self.host_functions.insert(function);
+    }
+
+    /// Returns the [`Vm`] with the given host function registered.
+    pub fn with_host_function(mut self, function: HostFunction) -> Self {
+        self.register_host_function(function);
+        self
+    }
+
+    pub(crate) fn host_functions(&self) -> &HostImportsResolver {
+        &self.host_functions
+    }
+
+    /// Sets the sink debug messages of contracts are sent to. Defaults to
+    /// [`StdoutSink`].
+    pub fn set_debug_sink(&mut self, sink: impl DebugSink + 'static) {
+        self.debug_sink = Arc::new(sink);
+    }
+
+    /// Returns the [`Vm`] with the given debug sink.
+    pub fn with_debug_sink(mut self, sink: impl DebugSink + 'static) -> Self {
+        self.set_debug_sink(sink);
+        self
+    }
+
+    pub(crate) fn debug_sink(&self) -> &dyn DebugSink {
+        self.debug_sink.as_ref()
+    }
+
+    /// Deploys a contract with its initial archived state, so other
+    /// contracts can call it. Its id is the blake2b hash of its code.
+    pub fn deploy(&mut self, code: &[u8], state: Vec<u8>) -> ContractId {
+        let mut id = [0u8; 32];
+        id.copy_from_slice(
+            blake2b_simd::Params::new()
+                .hash_length(32)
+                .to_state()
+                .update(code)
+                .finalize()
+                .as_bytes(),
+        );
+        let id = ContractId(id);
+
+        let contract = Contract {
+            code: Arc::from(code),
+            state,
+        };
+        self.contracts
+            .write()
+            .expect("Contract registry lock poisoned")
+            .insert(id, contract);
+
+        id
+    }
+
+    /// Returns the archived state of a deployed contract.
+    pub fn contract_state(&self, id: &ContractId) -> Option<Vec<u8>> {
+        self.contracts
+            .read()
+            .expect("Contract registry lock poisoned")
+            .get(id)
+            .map(|contract| contract.state.clone())
+    }
+
+    pub(crate) fn contract_code(&self, id: &ContractId) -> Option<Arc<[u8]>> {
+        self.contracts
+            .read()
+            .expect("Contract registry lock poisoned")
+            .get(id)
+            .map(|contract| contract.code.clone())
+    }
+
+    /// Persists the states of deployed contracts changed by a successful
+    /// execution.
+    fn commit_states(&self, states: BTreeMap<ContractId, Vec<u8>>) {
+        let mut contracts = self
+            .contracts
+            .write()
+            .expect("Contract registry lock poisoned");
+        for (id, state) in states {
+            if let Some(contract) = contracts.get_mut(&id) {
+                contract.state = state;
+            }
+        }
+    }
+
     /// Execute wasm with the given entrypoint.
     pub fn execute(
         &self,
         code: &[u8],
         entrypoint: &str,
         gas_meter: &mut GasMeter,
-    ) -> Result<(), VMError> {
+    ) -> Result<Receipt, VMError> {
+        self.execute_logged(code, entrypoint, gas_meter)
+            .map_err(|(e, _)| e)
+    }
+
+    /// Execute wasm with the given entrypoint, as [`Vm::execute`] does. A
+    /// failure is returned as an [`ErrorReceipt`], with the messages logged
+    /// before it.
+    pub fn execute_receipt(
+        &self,
+        code: &[u8],
+        entrypoint: &str,
+        gas_meter: &mut GasMeter,
+    ) -> Result<Receipt, ErrorReceipt> {
+        self.execute_logged(code, entrypoint, gas_meter)
+            .map_err(error_receipt)
+    }
+
+    fn execute_logged(
+        &self,
+        code: &[u8],
+        entrypoint: &str,
+        gas_meter: &mut GasMeter,
+    ) -> Result<Receipt, Failure> {
         let _span = trace_span!(
             "outer query",
             gas_limit = ?gas_meter.limit()
@@ -55,16 +196,164 @@ impl Vm {
         match context.execute(code, &entrypoint, gas_meter) {
             Ok(result) => {
                 trace!("query was successful");
-                Ok(result)
+                result
             }
             Err(e) => {
                 trace!("query returned an error: {}", e);
-                Err(e)
+                return Err((e, context.take_logs()));
+            }
+        };
+
+        self.commit_states(context.take_states());
+        Ok(context.into_receipt())
+    }
+
+    /// Call a `#[bindgen]` function taking arguments or returning a value,
+    /// passing it the archived `state` and `arg`. The archived return value
+    /// is in the receipt.
+    pub fn call(
+        &self,
+        code: &[u8],
+        entrypoint: &str,
+        state: &[u8],
+        arg: &[u8],
+        gas_meter: &mut GasMeter,
+    ) -> Result<Receipt, VMError> {
+        self.call_logged(code, entrypoint, state, arg, gas_meter)
+            .map_err(|(e, _)| e)
+    }
+
+    /// Call a `#[bindgen]` function, as [`Vm::call`] does. A failure is
+    /// returned as an [`ErrorReceipt`], with the messages logged before it.
+    pub fn call_receipt(
+        &self,
+        code: &[u8],
+        entrypoint: &str,
+        state: &[u8],
+        arg: &[u8],
+        gas_meter: &mut GasMeter,
+    ) -> Result<Receipt, ErrorReceipt> {
+        self.call_logged(code, entrypoint, state, arg, gas_meter)
+            .map_err(error_receipt)
+    }
+
+    fn call_logged(
+        &self,
+        code: &[u8],
+        entrypoint: &str,
+        state: &[u8],
+        arg: &[u8],
+        gas_meter: &mut GasMeter,
+    ) -> Result<Receipt, Failure> {
+        let _span = trace_span!(
+            "outer call",
+            gas_limit = ?gas_meter.limit()
+        );
+
+        let mut vm = self.clone();
+
+        let mut context = CallContext::new(&mut vm);
+
+        let entrypoint = format!("__vm_{}", entrypoint);
+        let ret = match context.call(code, &entrypoint, state, arg, gas_meter, true) {
+            Ok(ret) => {
+                trace!("call was successful");
+                ret
             }
-        }?;
+            Err(e) => {
+                trace!("call returned an error: {}", e);
+                return Err((e, context.take_logs()));
+            }
+        };
+
+        self.commit_states(context.take_states());
+        Ok(context.into_receipt().with_return(ret))
+    }
+
+    /// Run the query `Q` against a contract in the given state, through the
+    /// export named after [`Query::NAME`]. Use [`Vm::call`] for the full
+    /// receipt.
+    ///
+    /// The state and query are archived with an [`AllocSerializer`], while
+    /// the contract archives its return value with the `StoreSerializer` of
+    /// its store. Values holding store references, such as
+    /// `microkelvin::Link`, can only be archived in the guest, so pass them
+    /// to [`Vm::call`] already archived.
+    pub fn query<S, Q>(
+        &self,
+        code: &[u8],
+        state: &S,
+        query: Q,
+        gas_meter: &mut GasMeter,
+    ) -> Result<Q::Return, VMError>
+    where
+        S: Serialize<AllocSerializer<ARCHIVE_SCRATCH_SIZE>>,
+        Q: Query + Serialize<AllocSerializer<ARCHIVE_SCRATCH_SIZE>>,
+        Q::Return: Archive,
+        <Q::Return as Archive>::Archived:
+            for<'b> CheckBytes<DefaultValidator<'b>> + Deserialize<Q::Return, Infallible>,
+    {
+        let receipt = self.call(
+            code,
+            Q::NAME,
+            &archive(state)?,
+            &archive(&query)?,
+            gas_meter,
+        )?;
 
-        Ok(())
+        deserialize_archived(receipt.return_value().data())
     }
+
+    /// Apply the transaction `T` to a contract in the given state, through
+    /// the export named after [`Transaction::NAME`], updating the state. Use
+    /// [`Vm::call`] for the full receipt.
+    ///
+    /// As with [`Vm::query`], the state and transaction are archived with an
+    /// [`AllocSerializer`], so they cannot hold store references.
+    pub fn transact<S, T>(
+        &self,
+        code: &[u8],
+        state: &mut S,
+        transaction: T,
+        gas_meter: &mut GasMeter,
+    ) -> Result<T::Return, VMError>
+    where
+        S: Archive + Serialize<AllocSerializer<ARCHIVE_SCRATCH_SIZE>>,
+        S::Archived: for<'b> CheckBytes<DefaultValidator<'b>> + Deserialize<S, Infallible>,
+        T: Transaction + Serialize<AllocSerializer<ARCHIVE_SCRATCH_SIZE>>,
+        T::Return: Archive,
+        <T::Return as Archive>::Archived:
+            for<'b> CheckBytes<DefaultValidator<'b>> + Deserialize<T::Return, Infallible>,
+    {
+        let receipt = self.call(
+            code,
+            T::NAME,
+            &archive(&*state)?,
+            &archive(&transaction)?,
+            gas_meter,
+        )?;
+
+        let ret = deserialize_archived(receipt.return_value().data())?;
+        *state = deserialize_archived(receipt.return_value().state())?;
+
+        Ok(ret)
+    }
+}
+
+/// The error an execution failed with, and the messages logged before it
+type Failure = (VMError, Vec<LogRecord>);
+
+fn error_receipt((error, logs): Failure) -> ErrorReceipt {
+    ErrorReceipt::from(error).with_logs(logs)
+}
+
+fn archive<T>(value: &T) -> Result<Vec<u8>, VMError>
+where
+    T: Serialize<AllocSerializer<ARCHIVE_SCRATCH_SIZE>>,
+{
+    rkyv::to_bytes::<_, ARCHIVE_SCRATCH_SIZE>(value)
+        .map(|bytes| bytes.to_vec())
+        .map_err(|_| VMError::InvalidData)
 }
 
 impl Default for Vm {
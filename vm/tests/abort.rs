@@ -0,0 +1,64 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use vm::{GasMeter, VMError, Vm};
+
+const GAS_LIMIT: u64 = 1_000_000;
+
+/// A contract aborting with a message, with invalid UTF-8 in it, or with a
+/// message out of bounds.
+const ABORT: &str = r#"
+(module
+  (import "vm_v2" "abort" (func $abort (param i32 i32 i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "insufficient funds")
+  (data (i32.const 32) "bad \ff\fe bytes")
+  (func (export "__vm_abort")
+    (call $abort (i32.const -7) (i32.const 0) (i32.const 18)))
+  (func (export "__vm_invalid")
+    (call $abort (i32.const 3) (i32.const 32) (i32.const 12)))
+  (func (export "__vm_out_of_bounds")
+    (call $abort (i32.const 1) (i32.const 65530) (i32.const 16))))
+"#;
+
+fn abort(entrypoint: &str) -> VMError {
+    let mut gas_meter = GasMeter::with_limit(GAS_LIMIT);
+    Vm::new()
+        .execute(ABORT.as_bytes(), entrypoint, &mut gas_meter)
+        .expect_err("The contract should abort")
+}
+
+#[test]
+fn abort_with_message() {
+    let error = abort("abort");
+    assert_eq!(
+        error,
+        VMError::ContractAbort {
+            code: -7,
+            message: "insufficient funds".into(),
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "Contract aborted with code -7: insufficient funds"
+    );
+}
+
+#[test]
+fn abort_with_invalid_utf8() {
+    assert_eq!(
+        abort("invalid"),
+        VMError::ContractAbort {
+            code: 3,
+            message: "bad \u{fffd}\u{fffd} bytes".into(),
+        }
+    );
+}
+
+#[test]
+fn abort_with_message_out_of_bounds() {
+    assert_eq!(abort("out_of_bounds"), VMError::MemoryAccessOutOfBounds);
+}
@@ -0,0 +1,43 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use vm::{GasMeter, VMError, Vm};
+
+const GAS_LIMIT: u64 = 1_000_000;
+
+/// A contract trapping two calls deep, with its functions named in the name
+/// section.
+const TRAP: &str = r#"
+(module
+  (memory (export "memory") 1)
+  (func $fail unreachable)
+  (func $middle (call $fail))
+  (func $run (export "__vm_run") (call $middle)))
+"#;
+
+#[test]
+fn trap_backtrace_names_functions() {
+    let code = wat::parse_str(TRAP).expect("The module should assemble");
+
+    let mut gas_meter = GasMeter::with_limit(GAS_LIMIT);
+    let error = Vm::new()
+        .execute(&code, "run", &mut gas_meter)
+        .expect_err("The contract should trap");
+
+    assert!(matches!(error, VMError::WasmerTrap { .. }), "{:?}", error);
+
+    let backtrace = error.backtrace().expect("A trap has a backtrace");
+    let names: Vec<_> = backtrace
+        .frames()
+        .iter()
+        .map(|frame| frame.function_name())
+        .collect();
+    assert_eq!(names, vec![Some("fail"), Some("middle"), Some("run")]);
+    assert_eq!(backtrace.frames()[0].function_index(), 0);
+
+    let message = error.to_string();
+    assert!(message.contains("fail @"), "{}", message);
+}
@@ -0,0 +1,172 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use vm::primitives::{CallError, ContractId};
+use vm::{GasMeter, Vm};
+
+const GAS_LIMIT: u64 = 1_000_000;
+
+/// A contract whose `bump` transaction sets the first byte of its four byte
+/// state to 1.
+const COUNTER: &str = r#"
+(module
+  (memory (export "memory") 1)
+  (global (export "SCRATCH") i32 (i32.const 0))
+  (func (export "__vm_bump") (param $state_len i32) (param $end i32) (result i64)
+    (i32.store (i32.const 0) (i32.const 1))
+    (i64.const 0x400000004)))
+"#;
+
+/// A contract applying `bump` to the contract whose id is passed as
+/// argument, trapping if that fails.
+const RELAY: &str = r#"
+(module
+  (import "vm_v2" "transact" (func $transact (param i32 i32 i32 i32 i32 i64 i32 i32) (result i32)))
+  (memory (export "memory") 1)
+  (global (export "SCRATCH") i32 (i32.const 256))
+  (data (i32.const 0) "bump")
+  (func (export "__vm_relay") (param $state_len i32) (param $end i32) (result i64)
+    (if (call $transact
+          (i32.add (i32.const 256) (local.get $state_len)) (i32.const 0) (i32.const 4)
+          (i32.const 0) (i32.const 0) (i64.const 0) (i32.const 16) (i32.const 4))
+      (then unreachable))
+    (i64.extend_i32_u (local.get $state_len))))
+"#;
+
+/// A contract calling `relay` on the contract whose id is the first half of
+/// its argument, with the second half as argument, either as a query or as
+/// a transaction.
+const CALLER: &str = r#"
+(module
+  (import "vm_v2" "query" (func $query (param i32 i32 i32 i32 i32 i64 i32 i32) (result i32)))
+  (import "vm_v2" "transact" (func $transact (param i32 i32 i32 i32 i32 i64 i32 i32) (result i32)))
+  (memory (export "memory") 1)
+  (global (export "SCRATCH") i32 (i32.const 256))
+  (data (i32.const 0) "relay")
+  (func (export "__vm_query") (param $state_len i32) (param $end i32) (result i64)
+    (if (call $query
+          (i32.add (i32.const 256) (local.get $state_len)) (i32.const 0) (i32.const 5)
+          (i32.add (i32.const 288) (local.get $state_len)) (i32.const 32)
+          (i64.const 0) (i32.const 16) (i32.const 4))
+      (then unreachable))
+    (i64.extend_i32_u (local.get $state_len)))
+  (func (export "__vm_transact") (param $state_len i32) (param $end i32) (result i64)
+    (if (call $transact
+          (i32.add (i32.const 256) (local.get $state_len)) (i32.const 0) (i32.const 5)
+          (i32.add (i32.const 288) (local.get $state_len)) (i32.const 32)
+          (i64.const 0) (i32.const 16) (i32.const 4))
+      (then unreachable))
+    (i64.extend_i32_u (local.get $state_len))))
+"#;
+
+/// A contract either aborting with code 42 or returning eight bytes.
+const CALLEE: &str = r#"
+(module
+  (import "vm_v2" "abort" (func $abort (param i32 i32 i32)))
+  (memory (export "memory") 1)
+  (global (export "SCRATCH") i32 (i32.const 0))
+  (func (export "__vm_abort") (param $state_len i32) (param $end i32) (result i64)
+    (call $abort (i32.const 42) (i32.const 0) (i32.const 0))
+    unreachable)
+  (func (export "__vm_big") (param $state_len i32) (param $end i32) (result i64)
+    (i64.const 0x800000000)))
+"#;
+
+/// A contract calling [`CALLEE`], whose id is passed as argument, or a
+/// contract that doesn't exist, with a return buffer of a few bytes. It
+/// returns the code of the call and the first four bytes of the buffer.
+const PROBE: &str = r#"
+(module
+  (import "vm_v2" "transact" (func $transact (param i32 i32 i32 i32 i32 i64 i32 i32) (result i32)))
+  (memory (export "memory") 1)
+  (global (export "SCRATCH") i32 (i32.const 256))
+  (data (i32.const 0) "abort")
+  (data (i32.const 8) "big")
+  (func $probe (param $target i32) (param $name i32) (param $name_len i32) (param $buffer_len i32) (result i64)
+    (i32.store (i32.const 256)
+      (call $transact
+        (local.get $target) (local.get $name) (local.get $name_len)
+        (i32.const 0) (i32.const 0) (i64.const 0) (i32.const 64) (local.get $buffer_len)))
+    (i32.store (i32.const 260) (i32.load (i32.const 64)))
+    (i64.const 0x800000000))
+  (func (export "__vm_abort") (param $state_len i32) (param $end i32) (result i64)
+    (call $probe (i32.const 256) (i32.const 0) (i32.const 5) (i32.const 4)))
+  (func (export "__vm_abort_short_buffer") (param $state_len i32) (param $end i32) (result i64)
+    (call $probe (i32.const 256) (i32.const 0) (i32.const 5) (i32.const 3)))
+  (func (export "__vm_big") (param $state_len i32) (param $end i32) (result i64)
+    (call $probe (i32.const 256) (i32.const 8) (i32.const 3) (i32.const 4)))
+  (func (export "__vm_unknown") (param $state_len i32) (param $end i32) (result i64)
+    (call $probe (i32.const 128) (i32.const 0) (i32.const 5) (i32.const 4))))
+"#;
+
+/// Runs `entrypoint` of [`CALLER`], relaying to a freshly deployed counter,
+/// and returns the state of the counter afterwards.
+fn relay(entrypoint: &str) -> Vec<u8> {
+    let mut vm = Vm::new();
+    let counter = vm.deploy(COUNTER.as_bytes(), vec![0; 4]);
+    let relay = vm.deploy(RELAY.as_bytes(), vec![]);
+
+    let arg: Vec<u8> = [relay, counter].iter().flat_map(|id| id.0).collect();
+    let mut gas_meter = GasMeter::with_limit(GAS_LIMIT);
+    vm.call(CALLER.as_bytes(), entrypoint, &[], &arg, &mut gas_meter)
+        .expect("The call should succeed");
+
+    state(&vm, &counter)
+}
+
+fn state(vm: &Vm, id: &ContractId) -> Vec<u8> {
+    vm.contract_state(id)
+        .expect("The contract should be deployed")
+}
+
+#[test]
+fn transaction_changes_state() {
+    assert_eq!(relay("transact"), vec![1, 0, 0, 0]);
+}
+
+#[test]
+fn query_changes_no_state() {
+    assert_eq!(relay("query"), vec![0, 0, 0, 0]);
+}
+
+/// Runs `entrypoint` of [`PROBE`] and returns the code of the call along
+/// with the start of the return buffer.
+fn probe(entrypoint: &str) -> (i32, [u8; 4]) {
+    let mut vm = Vm::new();
+    let callee = vm.deploy(CALLEE.as_bytes(), vec![]);
+
+    let mut gas_meter = GasMeter::with_limit(GAS_LIMIT);
+    let receipt = vm
+        .call(PROBE.as_bytes(), entrypoint, &[], &callee.0, &mut gas_meter)
+        .expect("The prober should survive the failed call");
+
+    let data = receipt.return_value().data();
+    let mut code = [0; 4];
+    code.copy_from_slice(&data[..4]);
+    let mut buffer = [0; 4];
+    buffer.copy_from_slice(&data[4..]);
+    (i32::from_le_bytes(code), buffer)
+}
+
+#[test]
+fn abort_code_is_written_back() {
+    assert_eq!(probe("abort"), (CallError::ABORT, 42i32.to_le_bytes()));
+}
+
+#[test]
+fn abort_code_needs_four_bytes() {
+    assert_eq!(probe("abort_short_buffer"), (CallError::ABORT, [0; 4]));
+}
+
+#[test]
+fn unknown_contract() {
+    assert_eq!(probe("unknown"), (CallError::UNKNOWN_CONTRACT, [0; 4]));
+}
+
+#[test]
+fn return_too_large() {
+    assert_eq!(probe("big"), (CallError::RETURN_TOO_LARGE, [0; 4]));
+}
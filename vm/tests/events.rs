@@ -0,0 +1,80 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use vm::{GasMeter, Vm};
+
+const GAS_LIMIT: u64 = 1_000_000;
+
+/// A contract emitting an event, then either trapping or returning.
+const EMITTER: &str = r#"
+(module
+  (import "vm_v2" "emit" (func $emit (param i32 i32 i32 i32)))
+  (memory (export "memory") 1)
+  (global (export "SCRATCH") i32 (i32.const 256))
+  (data (i32.const 0) "lost")
+  (data (i32.const 8) "kept")
+  (func (export "__vm_fail") (param $state_len i32) (param $end i32) (result i64)
+    (call $emit (i32.const 0) (i32.const 4) (i32.const 0) (i32.const 0))
+    unreachable)
+  (func (export "__vm_succeed") (param $state_len i32) (param $end i32) (result i64)
+    (call $emit (i32.const 8) (i32.const 4) (i32.const 8) (i32.const 4))
+    (i64.extend_i32_u (local.get $state_len))))
+"#;
+
+/// A contract emitting events around a failing and a successful call to
+/// the contract whose id is passed as argument.
+const CALLER: &str = r#"
+(module
+  (import "vm_v2" "emit" (func $emit (param i32 i32 i32 i32)))
+  (import "vm_v2" "transact" (func $transact (param i32 i32 i32 i32 i32 i64 i32 i32) (result i32)))
+  (memory (export "memory") 1)
+  (global (export "SCRATCH") i32 (i32.const 256))
+  (data (i32.const 0) "before")
+  (data (i32.const 8) "after")
+  (data (i32.const 16) "fail")
+  (data (i32.const 24) "succeed")
+  (func (export "__vm_run") (param $state_len i32) (param $end i32) (result i64)
+    (call $emit (i32.const 0) (i32.const 6) (i32.const 0) (i32.const 0))
+    (if (i32.ge_s
+          (call $transact
+            (i32.const 256) (i32.const 16) (i32.const 4) (i32.const 0) (i32.const 0)
+            (i64.const 0) (i32.const 64) (i32.const 4))
+          (i32.const 0))
+      (then unreachable))
+    (if (call $transact
+          (i32.const 256) (i32.const 24) (i32.const 7) (i32.const 0) (i32.const 0)
+          (i64.const 0) (i32.const 64) (i32.const 4))
+      (then unreachable))
+    (call $emit (i32.const 8) (i32.const 5) (i32.const 0) (i32.const 0))
+    (i64.const 0)))
+"#;
+
+#[test]
+fn events_of_failed_calls_are_dropped() {
+    let mut vm = Vm::new();
+    let emitter = vm.deploy(EMITTER.as_bytes(), vec![]);
+
+    let mut gas_meter = GasMeter::with_limit(GAS_LIMIT);
+    let receipt = vm
+        .call(CALLER.as_bytes(), "run", &[], &emitter.0, &mut gas_meter)
+        .expect("The caller should survive the failed call");
+
+    let events: Vec<_> = receipt
+        .events()
+        .iter()
+        .map(|event| (event.call(), event.topic(), event.data()))
+        .collect();
+
+    // the failed call is the second one made
+    assert_eq!(
+        events,
+        vec![
+            (0, "before", &[][..]),
+            (2, "kept", &b"kept"[..]),
+            (0, "after", &[][..]),
+        ]
+    );
+}
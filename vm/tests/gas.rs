@@ -0,0 +1,61 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use vm::primitives::ContractId;
+use vm::{GasMeter, Vm};
+
+const GAS_LIMIT: u64 = 1_000_000;
+const CALLEE_GAS_LIMIT: u64 = 100_000;
+
+/// A contract looping until it runs out of gas.
+const SPIN: &str = r#"
+(module
+  (memory (export "memory") 1)
+  (global (export "SCRATCH") i32 (i32.const 0))
+  (func (export "__vm_spin") (param i32 i32) (result i64)
+    (loop br 0)
+    (i64.const 0)))
+"#;
+
+/// A contract querying `spin` on `target` with [`CALLEE_GAS_LIMIT`].
+fn caller(target: &ContractId) -> String {
+    let target: String = target.0.iter().map(|b| format!("\\{:02x}", b)).collect();
+
+    format!(
+        r#"
+(module
+  (import "vm_v2" "query"
+    (func $query (param i32 i32 i32 i32 i32 i64 i32 i32) (result i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "{}")
+  (data (i32.const 32) "spin")
+  (func (export "__vm_call_spin")
+    (drop
+      (call $query
+        (i32.const 0) (i32.const 32) (i32.const 4) (i32.const 64) (i32.const 0)
+        (i64.const {}) (i32.const 64) (i32.const 64)))))
+"#,
+        target, CALLEE_GAS_LIMIT
+    )
+}
+
+#[test]
+fn caller_pays_for_callee_running_out_of_gas() {
+    let mut vm = Vm::new();
+    let spin = vm.deploy(SPIN.as_bytes(), vec![]);
+    let caller = caller(&spin);
+
+    let mut gas_meter = GasMeter::with_limit(GAS_LIMIT);
+    vm.execute(caller.as_bytes(), "call_spin", &mut gas_meter)
+        .expect("The caller should survive the callee running out of gas");
+
+    assert!(
+        gas_meter.spent() >= CALLEE_GAS_LIMIT,
+        "Caller spent {}, less than the gas limit of the callee",
+        gas_meter.spent()
+    );
+    assert!(gas_meter.spent() < GAS_LIMIT);
+}
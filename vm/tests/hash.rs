@@ -0,0 +1,79 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use vm::{Config, GasMeter, VMError, Vm};
+
+const GAS_LIMIT: u64 = 1_000_000;
+
+/// A contract opening sha256 hashers, up to the limit and past it, and
+/// streaming "abc" into one.
+const HASH: &str = r#"
+(module
+  (import "crypto" "hash_init" (func $init (param i32) (result i32)))
+  (import "crypto" "hash_update" (func $update (param i32 i32 i32)))
+  (import "crypto" "hash_finalize" (func $finalize (param i32 i32 i32) (result i32)))
+  (memory (export "memory") 1)
+  (global (export "SCRATCH") i32 (i32.const 0))
+  (data (i32.const 128) "abc")
+  (func $open (param $n i32)
+    (loop $next
+      (drop (call $init (i32.const 0)))
+      (br_if $next (local.tee $n (i32.sub (local.get $n) (i32.const 1))))))
+  (func (export "__vm_open_all")
+    (call $open (i32.const 16)))
+  (func (export "__vm_open_too_many")
+    (call $open (i32.const 17)))
+  (func (export "__vm_reopen")
+    (call $open (i32.const 16))
+    (drop (call $finalize (i32.const 3) (i32.const 0) (i32.const 64)))
+    (call $open (i32.const 1)))
+  (func (export "__vm_sha256") (param $state_len i32) (param $end i32) (result i64)
+    (local $handle i32)
+    (local.set $handle (call $init (i32.const 0)))
+    (call $update (local.get $handle) (i32.const 128) (i32.const 1))
+    (call $update (local.get $handle) (i32.const 129) (i32.const 2))
+    (i64.shl
+      (i64.extend_i32_u (call $finalize (local.get $handle) (i32.const 0) (i32.const 64)))
+      (i64.const 32))))
+"#;
+
+static CONFIG: Config = Config::new();
+
+fn execute(entrypoint: &str) -> Result<u64, VMError> {
+    let mut gas_meter = GasMeter::with_limit(GAS_LIMIT);
+    Vm::with_config(&CONFIG).execute(HASH.as_bytes(), entrypoint, &mut gas_meter)?;
+    Ok(gas_meter.spent())
+}
+
+#[test]
+fn open_hashers_are_capped() {
+    assert_eq!(CONFIG.max_open_hashers, 16);
+
+    let spent = execute("open_all").expect("16 hashers can be open at once");
+    assert!(spent >= 16 * CONFIG.host_costs.hash_handle);
+
+    assert_eq!(execute("open_too_many"), Err(VMError::TooManyHandles));
+    execute("reopen").expect("A finalized hasher no longer counts");
+}
+
+#[test]
+fn streamed_sha256() {
+    let mut gas_meter = GasMeter::with_limit(GAS_LIMIT);
+    let receipt = Vm::new()
+        .call(HASH.as_bytes(), "sha256", &[], &[], &mut gas_meter)
+        .expect("The contract should run");
+
+    let digest: String = receipt
+        .return_value()
+        .data()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    assert_eq!(
+        digest,
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+}
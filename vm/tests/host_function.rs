@@ -0,0 +1,74 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use std::sync::{Arc, Mutex};
+
+use vm::{GasMeter, HostFunction, VMError, Vm};
+
+const GAS_LIMIT: u64 = 1_000_000;
+const COST: u64 = 5_000;
+
+/// A contract passing 21 to `double`, imported from a custom namespace, and
+/// returning the result.
+const DOUBLE: &str = r#"
+(module
+  (import "custom" "double" (func $double (param i64) (result i64)))
+  (memory (export "memory") 1)
+  (global (export "SCRATCH") i32 (i32.const 0))
+  (func (export "__vm_double") (param $state_len i32) (param $end i32) (result i64)
+    (i64.store (i32.const 0) (call $double (i64.const 21)))
+    (i64.const 0x800000000)))
+"#;
+
+/// Returns a [`Vm`] with `custom::double` registered at the given cost,
+/// along with the values it was called with.
+fn vm(cost: u64) -> (Vm, Arc<Mutex<Vec<i64>>>) {
+    let calls = Arc::new(Mutex::new(vec![]));
+    let recorded = calls.clone();
+
+    let double = HostFunction::new("double", move |_, (x,): (i64,)| {
+        recorded.lock().unwrap().push(x);
+        Ok(x * 2)
+    })
+    .namespace("custom")
+    .cost(cost);
+
+    (Vm::new().with_host_function(double), calls)
+}
+
+fn double(vm: &Vm, gas_limit: u64) -> Result<(i64, u64), VMError> {
+    let mut gas_meter = GasMeter::with_limit(gas_limit);
+    let receipt = vm.call(DOUBLE.as_bytes(), "double", &[], &[], &mut gas_meter)?;
+
+    let mut result = [0u8; 8];
+    result.copy_from_slice(receipt.return_value().data());
+    Ok((i64::from_le_bytes(result), gas_meter.spent()))
+}
+
+#[test]
+fn custom_host_function() {
+    let (vm, calls) = vm(COST);
+
+    let (result, _) = double(&vm, GAS_LIMIT).expect("The contract should run");
+    assert_eq!(result, 42);
+    assert_eq!(*calls.lock().unwrap(), vec![21]);
+}
+
+#[test]
+fn custom_host_function_cost() {
+    let (free, _) = vm(0);
+    let (charged, _) = vm(COST);
+
+    let (_, spent_free) = double(&free, GAS_LIMIT).unwrap();
+    let (_, spent_charged) = double(&charged, GAS_LIMIT).unwrap();
+    assert_eq!(spent_charged - spent_free, COST);
+
+    // the cost is charged before the function runs
+    let (charged, calls) = vm(COST);
+    let result = double(&charged, COST - 1);
+    assert!(matches!(result, Err(VMError::OutOfGas)), "{:?}", result);
+    assert!(calls.lock().unwrap().is_empty());
+}
@@ -0,0 +1,185 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use vm::primitives::LogLevel;
+use vm::{BufferSink, Config, GasMeter, LogRecord, NoopSink, Vm};
+
+const GAS_LIMIT: u64 = 1_000_000;
+
+/// A contract writing debug messages: a greeting, the whole of its memory,
+/// and invalid UTF-8.
+const DEBUG: &str = r#"
+(module
+  (import "env" "debug" (func $debug (param i32 i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "hello")
+  (data (i32.const 16) "\ff\fe")
+  (func (export "__vm_hello")
+    (call $debug (i32.const 0) (i32.const 5)))
+  (func (export "__vm_memory")
+    (call $debug (i32.const 0) (i32.const 65536)))
+  (func (export "__vm_invalid")
+    (call $debug (i32.const 16) (i32.const 2))))
+"#;
+
+/// A contract logging through `log`: a greeting at each level, the same
+/// greeting four times, or once before trapping.
+const LOG: &str = r#"
+(module
+  (import "vm_v2" "log" (func $log (param i32 i32 i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "hello")
+  (func $hello (param $level i32)
+    (call $log (local.get $level) (i32.const 0) (i32.const 5)))
+  (func (export "__vm_levels")
+    (call $hello (i32.const 0))
+    (call $hello (i32.const 1))
+    (call $hello (i32.const 2))
+    (call $hello (i32.const 3))
+    (call $hello (i32.const 4)))
+  (func (export "__vm_repeat")
+    (call $hello (i32.const 2))
+    (call $hello (i32.const 2))
+    (call $hello (i32.const 2))
+    (call $hello (i32.const 2)))
+  (func (export "__vm_trap")
+    (call $hello (i32.const 4))
+    unreachable))
+"#;
+
+static CAPPED: Config = Config {
+    max_debug_bytes: 16,
+    ..Config::new()
+};
+
+static DEFAULT: Config = Config::new();
+
+static INFO: Config = Config {
+    min_log_level: LogLevel::Info,
+    ..Config::new()
+};
+
+/// Runs `entrypoint` of [`DEBUG`], returning the debug output and the gas
+/// spent.
+fn debug(config: &'static Config, entrypoint: &str) -> (Vec<String>, u64) {
+    let sink = BufferSink::new();
+    let vm = Vm::with_config(config).with_debug_sink(sink.clone());
+
+    let mut gas_meter = GasMeter::with_limit(GAS_LIMIT);
+    let receipt = vm
+        .execute(DEBUG.as_bytes(), entrypoint, &mut gas_meter)
+        .expect("The contract should run");
+
+    let logs: Vec<_> = receipt.logs().iter().map(|log| log.message()).collect();
+    assert_eq!(logs, sink.messages());
+    (sink.take(), gas_meter.spent())
+}
+
+#[test]
+fn debug_message() {
+    let (messages, _) = debug(&CAPPED, "hello");
+    assert_eq!(messages, vec!["hello"]);
+}
+
+#[test]
+fn debug_charged_per_byte_even_when_dropped() {
+    let (messages, spent) = debug(&CAPPED, "memory");
+    assert!(messages.is_empty());
+
+    let per_byte = CAPPED.host_costs.log_per_byte;
+    assert!(spent >= 65536 * per_byte, "Spent only {}", spent);
+}
+
+#[test]
+fn filtered_debug_message_is_not_read() {
+    let (messages, _) = debug(&INFO, "invalid");
+    assert!(messages.is_empty());
+}
+
+fn levels(records: &[LogRecord]) -> Vec<(LogLevel, &str)> {
+    records
+        .iter()
+        .map(|record| (record.level(), record.message()))
+        .collect()
+}
+
+/// Runs `entrypoint` of [`LOG`], returning the records of the receipt and
+/// of a [`BufferSink`].
+fn log(config: &'static Config, entrypoint: &str) -> (Vec<LogRecord>, Vec<LogRecord>) {
+    let sink = BufferSink::new();
+    let vm = Vm::with_config(config).with_debug_sink(sink.clone());
+
+    let mut gas_meter = GasMeter::with_limit(GAS_LIMIT);
+    let receipt = vm
+        .execute(LOG.as_bytes(), entrypoint, &mut gas_meter)
+        .expect("The contract should run");
+
+    (receipt.logs().to_vec(), sink.take_records())
+}
+
+#[test]
+fn buffer_sink_records_levels() {
+    let (logs, records) = log(&DEFAULT, "levels");
+    assert_eq!(logs, records);
+    assert_eq!(
+        levels(&records),
+        vec![
+            (LogLevel::Trace, "hello"),
+            (LogLevel::Debug, "hello"),
+            (LogLevel::Info, "hello"),
+            (LogLevel::Warn, "hello"),
+            (LogLevel::Error, "hello"),
+        ]
+    );
+}
+
+#[test]
+fn min_log_level_filters() {
+    let (logs, records) = log(&INFO, "levels");
+    assert_eq!(logs, records);
+    assert_eq!(
+        levels(&records),
+        vec![
+            (LogLevel::Info, "hello"),
+            (LogLevel::Warn, "hello"),
+            (LogLevel::Error, "hello"),
+        ]
+    );
+}
+
+#[test]
+fn buffer_sink_with_byte_cap() {
+    // three greetings fit the 16 bytes, the fourth doesn't
+    let (logs, records) = log(&CAPPED, "repeat");
+    assert_eq!(logs, records);
+    assert_eq!(levels(&records), vec![(LogLevel::Info, "hello"); 3]);
+}
+
+#[test]
+fn noop_sink_keeps_receipt_logs() {
+    let vm = Vm::new().with_debug_sink(NoopSink);
+
+    let mut gas_meter = GasMeter::with_limit(GAS_LIMIT);
+    let receipt = vm
+        .execute(LOG.as_bytes(), "repeat", &mut gas_meter)
+        .expect("The contract should run");
+    assert_eq!(receipt.logs().len(), 4);
+}
+
+#[test]
+fn error_receipt_has_logs() {
+    let sink = BufferSink::new();
+    let vm = Vm::new().with_debug_sink(sink.clone());
+
+    let mut gas_meter = GasMeter::with_limit(GAS_LIMIT);
+    let receipt = vm
+        .execute_receipt(LOG.as_bytes(), "trap", &mut gas_meter)
+        .expect_err("The contract should trap");
+
+    assert_eq!(receipt.message(), "WASMER trap: unreachable");
+    assert_eq!(levels(receipt.logs()), vec![(LogLevel::Error, "hello")]);
+    assert_eq!(receipt.logs(), sink.records());
+}
@@ -0,0 +1,92 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use vm::{GasMeter, VMError, Vm};
+
+const GAS_LIMIT: u64 = 1_000_000;
+
+const SHA256_ABC: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+/// A contract hashing "abc" with `sha256` imported from `namespace`,
+/// returning the `output_len` bytes written.
+fn sha256(namespace: &str, output_len: u64) -> String {
+    format!(
+        r#"
+(module
+  (import "{}" "sha256" (func $sha256 (param i32 i32 i32)))
+  (memory (export "memory") 1)
+  (global (export "SCRATCH") i32 (i32.const 0))
+  (data (i32.const 128) "abc")
+  (func (export "__vm_sha256") (param $state_len i32) (param $end i32) (result i64)
+    (call $sha256 (i32.const 128) (i32.const 3) (i32.const 0))
+    (i64.const {})))
+"#,
+        namespace,
+        output_len << 32
+    )
+}
+
+/// A contract importing `name` from `namespace`, without calling it.
+fn importing(namespace: &str, name: &str) -> String {
+    format!(
+        r#"
+(module
+  (import "{}" "{}" (func (param i32 i32 i32)))
+  (memory (export "memory") 1)
+  (func (export "__vm_run")))
+"#,
+        namespace, name
+    )
+}
+
+fn hash(namespace: &str, output_len: u64) -> Vec<u8> {
+    let code = sha256(namespace, output_len);
+
+    let mut gas_meter = GasMeter::with_limit(GAS_LIMIT);
+    let receipt = Vm::new()
+        .call(code.as_bytes(), "sha256", &[], &[], &mut gas_meter)
+        .expect("The contract should run");
+    receipt.return_value().data().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[test]
+fn sha256_v1_writes_hex() {
+    assert_eq!(hash("env", 64), SHA256_ABC.as_bytes());
+    assert_eq!(hash("vm_v1", 64), SHA256_ABC.as_bytes());
+}
+
+#[test]
+fn sha256_v2_writes_raw_digest() {
+    assert_eq!(hex(&hash("vm_v2", 32)), SHA256_ABC);
+}
+
+#[test]
+fn unknown_imports_fail_instantiation() {
+    for (namespace, name) in [("env", "log"), ("vm_v1", "abort"), ("nowhere", "debug")] {
+        let code = importing(namespace, name);
+
+        let mut gas_meter = GasMeter::with_limit(GAS_LIMIT);
+        match Vm::new().execute(code.as_bytes(), "run", &mut gas_meter) {
+            Err(VMError::WasmerInstantiationError(message)) => {
+                assert!(
+                    message.contains(&format!("{}::{}", namespace, name)),
+                    "{}",
+                    message
+                )
+            }
+            result => panic!("{}::{} resolved: {:?}", namespace, name, result),
+        }
+    }
+
+    let mut gas_meter = GasMeter::with_limit(GAS_LIMIT);
+    Vm::new()
+        .execute(importing("vm_v2", "log").as_bytes(), "run", &mut gas_meter)
+        .expect("vm_v2::log should resolve");
+}
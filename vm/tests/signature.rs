@@ -0,0 +1,130 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use vm::primitives::Ed25519BatchItem;
+use vm::{GasMeter, Vm};
+
+const GAS_LIMIT: u64 = 1_000_000;
+
+/// A contract verifying the archived batch passed as argument, returning
+/// the `i32` result of `verify_ed25519_batch`.
+const VERIFY_BATCH: &str = r#"
+(module
+  (import "crypto" "verify_ed25519_batch" (func $verify (param i32 i32) (result i32)))
+  (memory (export "memory") 1)
+  (global (export "SCRATCH") i32 (i32.const 0))
+  (func (export "__vm_verify") (param $state_len i32) (param $end i32) (result i64)
+    (i32.store (i32.const 0)
+      (call $verify (local.get $state_len) (i32.sub (local.get $end) (local.get $state_len))))
+    (i64.const 0x400000000)))
+"#;
+
+/// Public key, message and signature of the tests in RFC 8032, 7.1
+const RFC8032: [(&str, &str, &str); 3] = [
+    (
+        "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        "",
+        "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e06522490155\
+         5fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+    ),
+    (
+        "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+        "72",
+        "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da\
+         085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00",
+    ),
+    (
+        "fc51cd8e6218a1a38da47ed00230f0580816ed13ba3303ac5deb911548908025",
+        "af82",
+        "6291d657deec24024827e69c3abe01a30ce548a284743a445e3680d7db5ac3ac\
+         18ff9b538d16f290ae67f760984dc6594a7c15e9716ed28dc027beceea1ec40a",
+    ),
+];
+
+fn hex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn array<const N: usize>(s: &str) -> [u8; N] {
+    let mut array = [0u8; N];
+    array.copy_from_slice(&hex(s));
+    array
+}
+
+fn rfc8032_batch() -> Vec<Ed25519BatchItem> {
+    RFC8032
+        .iter()
+        .map(|(pk, msg, sig)| Ed25519BatchItem::new(array(pk), hex(msg), array(sig)))
+        .collect()
+}
+
+/// Returns the result of verifying `items` and the gas spent.
+fn verify(items: Vec<Ed25519BatchItem>) -> (i32, u64) {
+    let arg = rkyv::to_bytes::<_, 1024>(&items).unwrap();
+
+    let mut gas_meter = GasMeter::with_limit(GAS_LIMIT);
+    let receipt = Vm::new()
+        .call(VERIFY_BATCH.as_bytes(), "verify", &[], &arg, &mut gas_meter)
+        .expect("The batch should be verified");
+
+    let mut result = [0u8; 4];
+    result.copy_from_slice(receipt.return_value().data());
+    (i32::from_le_bytes(result), gas_meter.spent())
+}
+
+#[test]
+fn valid_batch() {
+    let (result, _) = verify(rfc8032_batch());
+    assert_eq!(result, -1);
+
+    let (result, _) = verify(vec![]);
+    assert_eq!(result, -1);
+}
+
+#[test]
+fn first_invalid_item() {
+    let mut items = rfc8032_batch();
+    items[1].sig[0] ^= 1;
+    items[2].msg = b"wrong message".to_vec();
+
+    let (result, spent) = verify(items);
+    assert_eq!(result, 1);
+
+    let (_, spent_valid) = verify(rfc8032_batch());
+    assert!(
+        spent > spent_valid,
+        "A failed batch is charged the fallback"
+    );
+}
+
+#[test]
+fn weak_keys() {
+    // the identity as key and commitment, with a zero scalar, satisfies the
+    // batch equation for any message, but not the strict one
+    let mut identity = [0u8; 32];
+    identity[0] = 1;
+    let mut sig = [0u8; 64];
+    sig[..32].copy_from_slice(&identity);
+
+    let weak = vec![
+        Ed25519BatchItem::new(identity, b"any message".to_vec(), sig),
+        Ed25519BatchItem::new(identity, b"another message".to_vec(), sig),
+    ];
+    let (result, _) = verify(weak);
+    assert_eq!(result, 0);
+
+    let mut items = rfc8032_batch();
+    items.push(Ed25519BatchItem::new(
+        identity,
+        b"any message".to_vec(),
+        sig,
+    ));
+    let (result, _) = verify(items);
+    assert_eq!(result, 3);
+}
@@ -0,0 +1,143 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use dusk_bls12_381::{BlsScalar, G1Affine, G2Affine};
+use dusk_bytes::Serializable;
+use dusk_jubjub::{JubJubAffine, JubJubScalar, GENERATOR, GENERATOR_EXTENDED};
+use vm::{GasMeter, Vm};
+
+const GAS_LIMIT: u64 = 100_000_000;
+
+/// A contract passing its argument to the zk host functions. Each export
+/// returns the `i32` result of the function, followed by the 32 bytes it
+/// wrote if any.
+const ZK: &str = r#"
+(module
+  (import "crypto" "poseidon_hash" (func $poseidon_hash (param i32 i32 i32) (result i32)))
+  (import "crypto" "jubjub_add" (func $jubjub_add (param i32 i32 i32) (result i32)))
+  (import "crypto" "jubjub_mul" (func $jubjub_mul (param i32 i32 i32) (result i32)))
+  (import "crypto" "bls_pairing_check" (func $bls_pairing_check (param i32 i32) (result i32)))
+  (memory (export "memory") 1)
+  (global (export "SCRATCH") i32 (i32.const 0))
+  (func (export "__vm_poseidon_hash") (param $state_len i32) (param $end i32) (result i64)
+    (i32.store (i32.const 0)
+      (call $poseidon_hash (i32.const 0) (i32.div_u (local.get $end) (i32.const 32)) (i32.const 4)))
+    (i64.const 0x2400000000))
+  (func (export "__vm_jubjub_add") (param $state_len i32) (param $end i32) (result i64)
+    (i32.store (i32.const 0)
+      (call $jubjub_add (i32.const 0) (i32.const 32) (i32.const 4)))
+    (i64.const 0x2400000000))
+  (func (export "__vm_jubjub_mul") (param $state_len i32) (param $end i32) (result i64)
+    (i32.store (i32.const 0)
+      (call $jubjub_mul (i32.const 0) (i32.const 32) (i32.const 4)))
+    (i64.const 0x2400000000))
+  (func (export "__vm_bls_pairing_check") (param $state_len i32) (param $end i32) (result i64)
+    (i32.store (i32.const 0)
+      (call $bls_pairing_check (i32.const 0) (i32.div_u (local.get $end) (i32.const 144))))
+    (i64.const 0x400000000)))
+"#;
+
+/// Runs `entrypoint` of [`ZK`] with `arg`, returning the result of the host
+/// function and the bytes written after it.
+fn zk(entrypoint: &str, arg: &[u8]) -> (i32, Vec<u8>) {
+    let mut gas_meter = GasMeter::with_limit(GAS_LIMIT);
+    let receipt = Vm::new()
+        .call(ZK.as_bytes(), entrypoint, &[], arg, &mut gas_meter)
+        .expect("The host function should run");
+
+    let data = receipt.return_value().data();
+    let mut result = [0u8; 4];
+    result.copy_from_slice(&data[..4]);
+    (i32::from_le_bytes(result), data[4..].to_vec())
+}
+
+fn pair(g1: G1Affine, g2: G2Affine) -> Vec<u8> {
+    let mut pair = g1.to_bytes().to_vec();
+    pair.extend_from_slice(&g2.to_bytes());
+    pair
+}
+
+#[test]
+fn poseidon_hash() {
+    let scalars = [BlsScalar::from(1), BlsScalar::from(2), BlsScalar::from(3)];
+    let arg: Vec<u8> = scalars.iter().flat_map(|s| s.to_bytes()).collect();
+
+    let (result, hash) = zk("poseidon_hash", &arg);
+    assert_eq!(result, 1);
+    assert_eq!(hash, dusk_poseidon::sponge::hash(&scalars).to_bytes());
+}
+
+#[test]
+fn poseidon_hash_of_non_canonical_scalar() {
+    let (result, _) = zk("poseidon_hash", &[0xff; 32]);
+    assert_eq!(result, 0);
+}
+
+#[test]
+fn jubjub_mul_generator() {
+    let scalar = JubJubScalar::from(42u64);
+    let mut arg = GENERATOR.to_bytes().to_vec();
+    arg.extend_from_slice(&scalar.to_bytes());
+
+    let (result, product) = zk("jubjub_mul", &arg);
+    assert_eq!(result, 1);
+    assert_eq!(
+        product,
+        JubJubAffine::from(GENERATOR_EXTENDED * scalar).to_bytes()
+    );
+}
+
+#[test]
+fn jubjub_add_generator() {
+    let mut arg = GENERATOR.to_bytes().to_vec();
+    arg.extend_from_slice(&GENERATOR.to_bytes());
+
+    let (result, sum) = zk("jubjub_add", &arg);
+    assert_eq!(result, 1);
+    assert_eq!(
+        sum,
+        JubJubAffine::from(GENERATOR_EXTENDED * JubJubScalar::from(2u64)).to_bytes()
+    );
+}
+
+#[test]
+fn jubjub_malformed_point() {
+    let mut arg = vec![0xff; 32];
+    arg.extend_from_slice(&JubJubScalar::one().to_bytes());
+
+    let (result, _) = zk("jubjub_mul", &arg);
+    assert_eq!(result, 0);
+
+    let (result, _) = zk("jubjub_add", &[0xff; 64]);
+    assert_eq!(result, 0);
+}
+
+#[test]
+fn bls_pairing_check() {
+    let (g1, g2) = (G1Affine::generator(), G2Affine::generator());
+
+    // e(g1, g2) * e(-g1, g2) is the identity
+    let mut valid = pair(g1, g2);
+    valid.extend(pair(-g1, g2));
+    let (result, _) = zk("bls_pairing_check", &valid);
+    assert_eq!(result, 1);
+
+    let mut invalid = pair(g1, g2);
+    invalid.extend(pair(g1, g2));
+    let (result, _) = zk("bls_pairing_check", &invalid);
+    assert_eq!(result, 0);
+}
+
+#[test]
+fn bls_pairing_check_malformed_point() {
+    let mut pairs = pair(G1Affine::generator(), G2Affine::generator());
+    pairs.extend(pair(-G1Affine::generator(), G2Affine::generator()));
+    let second = G1Affine::SIZE + G2Affine::SIZE;
+    pairs[second..second + G1Affine::SIZE].fill(0xff);
+
+    let (result, _) = zk("bls_pairing_check", &pairs);
+    assert_eq!(result, 0);
+}